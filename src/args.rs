@@ -1,4 +1,121 @@
 use argh::FromArgs;
+use std::str::FromStr;
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum LineEndingArg {
+    Lf,
+    Crlf,
+    Auto,
+}
+
+impl FromStr for LineEndingArg {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "lf" => Ok(LineEndingArg::Lf),
+            "crlf" => Ok(LineEndingArg::Crlf),
+            "auto" => Ok(LineEndingArg::Auto),
+            _ => Err("expected lf, crlf or auto".to_string()),
+        }
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum IndentStyleArg {
+    Space,
+    Tab,
+}
+
+impl FromStr for IndentStyleArg {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "space" => Ok(IndentStyleArg::Space),
+            "tab" => Ok(IndentStyleArg::Tab),
+            _ => Err("expected space or tab".to_string()),
+        }
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum IndentArg {
+    Auto,
+}
+
+impl FromStr for IndentArg {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(IndentArg::Auto),
+            _ => Err("expected auto".to_string()),
+        }
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum DialectArg {
+    Json,
+    Jsonc,
+    Json5,
+}
+
+impl FromStr for DialectArg {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(DialectArg::Json),
+            "jsonc" => Ok(DialectArg::Jsonc),
+            "json5" => Ok(DialectArg::Json5),
+            _ => Err("expected json, jsonc or json5".to_string()),
+        }
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum ErrorFormatArg {
+    Human,
+    Json,
+    Sarif,
+    Github,
+}
+
+impl FromStr for ErrorFormatArg {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "human" => Ok(ErrorFormatArg::Human),
+            "json" => Ok(ErrorFormatArg::Json),
+            "sarif" => Ok(ErrorFormatArg::Sarif),
+            "github" => Ok(ErrorFormatArg::Github),
+            _ => Err("expected human, json, sarif or github".to_string()),
+        }
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum ColorArg {
+    Auto,
+    Always,
+    Never,
+}
+
+impl FromStr for ColorArg {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(ColorArg::Auto),
+            "always" => Ok(ColorArg::Always),
+            "never" => Ok(ColorArg::Never),
+            _ => Err("expected auto, always or never".to_string()),
+        }
+    }
+}
 
 #[derive(FromArgs, PartialEq, Debug)]
 /// joxide, CLI tool for formatting and validating JSON files
@@ -18,16 +135,96 @@ pub enum JoxideSubcommand {
 /// format json file
 #[argh(subcommand, name = "format")]
 pub struct FormatArgs {
-    #[argh(option, default = "4")]
+    #[argh(option)]
     /// indent length, default is 4
-    pub indent_length: usize,
+    pub indent_length: Option<usize>,
+
+    #[argh(option)]
+    /// indent with space or tab, a tab per level, default is space
+    pub indent_style: Option<IndentStyleArg>,
+
+    #[argh(option)]
+    /// auto to keep the indent used in each file, falling back to the indent
+    /// length and style when a file isn't indented
+    pub indent: Option<IndentArg>,
 
     #[argh(switch)]
     /// modify the file instead of printing to console
     pub write: bool,
 
+    #[argh(switch)]
+    /// list the files that are not formatted instead of printing or writing them
+    pub check: bool,
+
+    #[argh(switch)]
+    /// print a diff of the changes instead of printing or writing the files
+    pub diff: bool,
+
+    #[argh(switch)]
+    /// sort object keys alphabetically instead of keeping their order
+    pub sort_keys: bool,
+
+    #[argh(switch)]
+    /// keep the order of object keys even when the config file sorts them
+    pub no_sort_keys: bool,
+
+    #[argh(option)]
+    /// keep arrays and objects that fit in this many columns on one line, default
+    /// is 0 to put every element on its own line
+    pub max_width: Option<usize>,
+
+    #[argh(option)]
+    /// line ending to use, lf, crlf or auto to keep the one in the file, default is auto
+    pub line_ending: Option<LineEndingArg>,
+
+    #[argh(option)]
+    /// input dialect, json, jsonc for comments and trailing commas or json5, default is json
+    pub dialect: Option<DialectArg>,
+
+    #[argh(switch)]
+    /// treat files as JSON Lines, one value per line
+    pub json_lines: bool,
+
+    #[argh(switch)]
+    /// treat files as a single value even when the config file or extension says
+    /// they are JSON Lines
+    pub no_json_lines: bool,
+
+    #[argh(option)]
+    /// maximum number of errors to report per file, 0 for no limit, default is 20
+    pub max_errors: Option<usize>,
+
+    #[argh(option, default = "ErrorFormatArg::Human")]
+    /// how to report errors, human, json, sarif or github, default is human
+    pub error_format: ErrorFormatArg,
+
+    #[argh(option, default = "ColorArg::Auto")]
+    /// when to use colors, auto, always or never, default is auto
+    pub color: ColorArg,
+
+    #[argh(option)]
+    /// file name to show in messages when reading from stdin
+    pub stdin_filename: Option<String>,
+
+    #[argh(option)]
+    /// skip files and directories matching the glob pattern, can be given more than once
+    pub exclude: Vec<String>,
+
+    #[argh(option)]
+    /// file extension to search directories for instead of the built-in ones, can
+    /// be given more than once
+    pub ext: Vec<String>,
+
+    #[argh(switch)]
+    /// search hidden directories like .vscode too
+    pub hidden: bool,
+
+    #[argh(switch)]
+    /// skip hidden directories even when the config file searches them
+    pub no_hidden: bool,
+
     #[argh(positional)]
-    /// list of paths to directory or file or unix glob pattern
+    /// list of paths to directory or file or unix glob pattern, - or nothing to read stdin
     pub paths: Vec<String>,
 }
 
@@ -35,7 +232,53 @@ pub struct FormatArgs {
 /// validate json file for syntax errors
 #[argh(subcommand, name = "validate")]
 pub struct ValidateArgs {
+    #[argh(option)]
+    /// input dialect, json, jsonc for comments and trailing commas or json5, default is json
+    pub dialect: Option<DialectArg>,
+
+    #[argh(switch)]
+    /// treat files as JSON Lines, one value per line
+    pub json_lines: bool,
+
+    #[argh(switch)]
+    /// treat files as a single value even when the config file or extension says
+    /// they are JSON Lines
+    pub no_json_lines: bool,
+
+    #[argh(option)]
+    /// maximum number of errors to report per file, 0 for no limit, default is 20
+    pub max_errors: Option<usize>,
+
+    #[argh(option, default = "ErrorFormatArg::Human")]
+    /// how to report errors, human, json, sarif or github, default is human
+    pub error_format: ErrorFormatArg,
+
+    #[argh(option, default = "ColorArg::Auto")]
+    /// when to use colors, auto, always or never, default is auto
+    pub color: ColorArg,
+
+    #[argh(option)]
+    /// file name to show in messages when reading from stdin
+    pub stdin_filename: Option<String>,
+
+    #[argh(option)]
+    /// skip files and directories matching the glob pattern, can be given more than once
+    pub exclude: Vec<String>,
+
+    #[argh(option)]
+    /// file extension to search directories for instead of the built-in ones, can
+    /// be given more than once
+    pub ext: Vec<String>,
+
+    #[argh(switch)]
+    /// search hidden directories like .vscode too
+    pub hidden: bool,
+
+    #[argh(switch)]
+    /// skip hidden directories even when the config file searches them
+    pub no_hidden: bool,
+
     #[argh(positional)]
-    /// list of paths to directory or file or unix glob pattern
+    /// list of paths to directory or file or unix glob pattern, - or nothing to read stdin
     pub paths: Vec<String>,
 }
@@ -0,0 +1,50 @@
+use std::{env, ffi::OsString, io::IsTerminal};
+
+use crate::args::ColorArg;
+
+pub const BOLD: &str = "\x1b[1m";
+pub const RED: &str = "\x1b[31m";
+pub const BOLD_RED: &str = "\x1b[1;31m";
+pub const GREEN: &str = "\x1b[32m";
+pub const BLUE: &str = "\x1b[34m";
+pub const CYAN: &str = "\x1b[36m";
+const RESET: &str = "\x1b[0m";
+
+// An empty NO_COLOR doesn't count, see https://no-color.org
+fn choose(color: ColorArg, no_color: Option<OsString>, is_terminal: bool) -> bool {
+    match color {
+        ColorArg::Always => true,
+        ColorArg::Never => false,
+        ColorArg::Auto => no_color.is_none_or(|value| value.is_empty()) && is_terminal,
+    }
+}
+
+/// Whether output written to `stream` should be colored
+pub fn enabled(color: ColorArg, stream: impl IsTerminal) -> bool {
+    choose(color, env::var_os("NO_COLOR"), stream.is_terminal())
+}
+
+pub fn paint(enabled: bool, style: &str, text: &str) -> String {
+    match enabled {
+        true => format!("{}{}{}", style, text, RESET),
+        false => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_choose() {
+        assert!(choose(ColorArg::Auto, None, true));
+        assert!(!choose(ColorArg::Auto, None, false));
+        assert!(!choose(ColorArg::Auto, Some("1".into()), true));
+        assert!(choose(ColorArg::Auto, Some("".into()), true));
+        assert!(choose(ColorArg::Always, Some("1".into()), false));
+        assert!(!choose(ColorArg::Never, None, true));
+
+        assert_eq!(paint(true, RED, "a"), "\x1b[31ma\x1b[0m");
+        assert_eq!(paint(false, RED, "a"), "a");
+    }
+}
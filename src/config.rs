@@ -0,0 +1,557 @@
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    path::{Path, PathBuf},
+    rc::Rc,
+    str::FromStr,
+};
+
+use crate::args::{DialectArg, IndentArg, IndentStyleArg, JoxideSubcommand, LineEndingArg};
+use glob::{MatchOptions, Pattern};
+use joxide::{diagnostic, lexer, parser, Json};
+use toml::{Table, Value};
+
+// Looked up in this order in every directory
+const FILE_NAMES: [&str; 2] = [".joxide.json", "joxide.toml"];
+
+/// Options that can be given on the command line and in a config file,
+/// `None` when not given. `exclude`, `ext` and `hidden` are only read at the
+/// top of a config file and apply to the directories searched under it, and
+/// --error-format and --color are only taken from the command line.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Settings {
+    pub indent_length: Option<usize>,
+    pub indent_style: Option<IndentStyleArg>,
+    pub indent: Option<IndentArg>,
+    pub max_width: Option<usize>,
+    pub sort_keys: Option<bool>,
+    pub line_ending: Option<LineEndingArg>,
+    pub dialect: Option<DialectArg>,
+    pub json_lines: Option<bool>,
+    pub max_errors: Option<usize>,
+    pub exclude: Option<Vec<String>>,
+    pub ext: Option<Vec<String>>,
+    pub hidden: Option<bool>,
+}
+
+impl Settings {
+    /// Takes the options that are not given from `base`. Giving the indent
+    /// length or style turns off `indent` from `base`.
+    pub fn or(self, base: &Settings) -> Settings {
+        let indent = match (self.indent_length, self.indent_style) {
+            (None, None) => self.indent.or(base.indent),
+            _ => self.indent,
+        };
+
+        Settings {
+            indent_length: self.indent_length.or(base.indent_length),
+            indent_style: self.indent_style.or(base.indent_style),
+            indent,
+            max_width: self.max_width.or(base.max_width),
+            sort_keys: self.sort_keys.or(base.sort_keys),
+            line_ending: self.line_ending.or(base.line_ending),
+            dialect: self.dialect.or(base.dialect),
+            json_lines: self.json_lines.or(base.json_lines),
+            max_errors: self.max_errors.or(base.max_errors),
+            exclude: self.exclude.or_else(|| base.exclude.clone()),
+            ext: self.ext.or_else(|| base.ext.clone()),
+            hidden: self.hidden.or(base.hidden),
+        }
+    }
+
+    pub fn from_args(sub_command: &JoxideSubcommand) -> Settings {
+        // Neither switch leaves the option to the config, `on` wins over `off`
+        let switch = |on: bool, off: bool| match (on, off) {
+            (true, _) => Some(true),
+            (false, true) => Some(false),
+            (false, false) => None,
+        };
+        let list = |values: &Vec<String>| (!values.is_empty()).then(|| values.clone());
+
+        match sub_command {
+            JoxideSubcommand::Format(format_args) => Settings {
+                indent_length: format_args.indent_length,
+                indent_style: format_args.indent_style,
+                indent: format_args.indent,
+                max_width: format_args.max_width,
+                sort_keys: switch(format_args.sort_keys, format_args.no_sort_keys),
+                line_ending: format_args.line_ending,
+                dialect: format_args.dialect,
+                json_lines: switch(format_args.json_lines, format_args.no_json_lines),
+                max_errors: format_args.max_errors,
+                exclude: list(&format_args.exclude),
+                ext: list(&format_args.ext),
+                hidden: switch(format_args.hidden, format_args.no_hidden),
+            },
+            JoxideSubcommand::Validate(validate_args) => Settings {
+                dialect: validate_args.dialect,
+                json_lines: switch(validate_args.json_lines, validate_args.no_json_lines),
+                max_errors: validate_args.max_errors,
+                exclude: list(&validate_args.exclude),
+                ext: list(&validate_args.ext),
+                hidden: switch(validate_args.hidden, validate_args.no_hidden),
+                ..Settings::default()
+            },
+        }
+    }
+}
+
+struct Override {
+    files: Vec<Pattern>,
+    settings: Settings,
+}
+
+struct Config {
+    dir: PathBuf,
+    settings: Settings,
+    overrides: Vec<Override>,
+}
+
+fn get_usize(key: &str, value: &Value) -> Result<usize, String> {
+    value
+        .as_integer()
+        .and_then(|integer| usize::try_from(integer).ok())
+        .ok_or_else(|| format!("'{}' should be a non-negative integer", key))
+}
+
+fn get_bool(key: &str, value: &Value) -> Result<bool, String> {
+    value
+        .as_bool()
+        .ok_or_else(|| format!("'{}' should be true or false", key))
+}
+
+fn get_arg<T: FromStr<Err = String>>(key: &str, value: &Value) -> Result<T, String> {
+    match value.as_str() {
+        Some(s) => T::from_str(s).map_err(|err| format!("'{}' {}", key, err)),
+        None => Err(format!("'{}' should be a string", key)),
+    }
+}
+
+fn get_strings(key: &str, value: &Value, kind: &str) -> Result<Vec<String>, String> {
+    let error = || format!("'{}' should be {} or a list of them", key, kind);
+    let values = match value {
+        Value::String(_) => std::slice::from_ref(value),
+        Value::Array(values) => values.as_slice(),
+        _ => return Err(error()),
+    };
+
+    values
+        .iter()
+        .map(|value| value.as_str().map(str::to_string).ok_or_else(error))
+        .collect()
+}
+
+fn get_patterns(key: &str, value: &Value) -> Result<Vec<Pattern>, String> {
+    get_strings(key, value, "a glob pattern")?
+        .iter()
+        .map(|s| Pattern::new(s).map_err(|err| format!("invalid pattern '{}', {}", s, err)))
+        .collect()
+}
+
+// Settings in `table`, `extra` is given the keys that aren't options
+fn get_settings(
+    table: &Table,
+    mut extra: impl FnMut(&str, &Value) -> Result<(), String>,
+) -> Result<Settings, String> {
+    let mut settings = Settings::default();
+
+    for (key, value) in table {
+        match key.as_str() {
+            "indent-length" => settings.indent_length = Some(get_usize(key, value)?),
+            "indent-style" => settings.indent_style = Some(get_arg(key, value)?),
+            "indent" => settings.indent = Some(get_arg(key, value)?),
+            "max-width" => settings.max_width = Some(get_usize(key, value)?),
+            "sort-keys" => settings.sort_keys = Some(get_bool(key, value)?),
+            "line-ending" => settings.line_ending = Some(get_arg(key, value)?),
+            "dialect" => settings.dialect = Some(get_arg(key, value)?),
+            "json-lines" => settings.json_lines = Some(get_bool(key, value)?),
+            "max-errors" => settings.max_errors = Some(get_usize(key, value)?),
+            "exclude" => {
+                let patterns = get_patterns(key, value)?;
+                settings.exclude = Some(patterns.iter().map(|p| p.as_str().to_string()).collect())
+            }
+            "ext" => settings.ext = Some(get_strings(key, value, "an extension")?),
+            "hidden" => settings.hidden = Some(get_bool(key, value)?),
+            _ => extra(key, value)?,
+        }
+    }
+
+    Ok(settings)
+}
+
+fn get_override(value: &Value) -> Result<Override, String> {
+    let table = value
+        .as_table()
+        .ok_or("every override should be an object")?;
+    let mut files = None;
+
+    let settings = get_settings(table, |key, value| match key {
+        "files" => {
+            files = Some(get_patterns(key, value)?);
+            Ok(())
+        }
+        _ => Err(format!("unknown option '{}'", key)),
+    })?;
+
+    if settings.exclude.is_some() || settings.ext.is_some() || settings.hidden.is_some() {
+        return Err("'exclude', 'ext' and 'hidden' can't be in an override".to_string());
+    }
+
+    match files {
+        Some(files) => Ok(Override { files, settings }),
+        None => Err("every override should have 'files'".to_string()),
+    }
+}
+
+fn json_to_toml(json: Json) -> Result<Value, String> {
+    match json {
+        Json::Null => Err("null is not a valid value".to_string()),
+        Json::Bool(b) => Ok(Value::Boolean(b)),
+        Json::Number(n) => match (n.as_i64(), n.as_f64()) {
+            (Some(integer), _) => Ok(Value::Integer(integer)),
+            (None, Some(float)) => Ok(Value::Float(float)),
+            (None, None) => Err(format!("{} is out of range", n.as_str())),
+        },
+        Json::String(s) => Ok(Value::String(s.into_owned())),
+        Json::Array(values) => values
+            .into_iter()
+            .map(json_to_toml)
+            .collect::<Result<_, _>>()
+            .map(Value::Array),
+        Json::Object(members) => members
+            .into_iter()
+            .map(|(key, value)| Ok((key.into_owned(), json_to_toml(value)?)))
+            .collect::<Result<_, _>>()
+            .map(Value::Table),
+    }
+}
+
+// Comments and trailing commas are allowed in .joxide.json
+fn parse_json(raw: &str) -> Result<Table, String> {
+    let tokens = lexer::lex_with(raw, lexer::Dialect::Jsonc);
+    let options = parser::ParseOptions {
+        max_errors: 1,
+        dialect: lexer::Dialect::Jsonc,
+    };
+
+    let value = parser::parse_with(&tokens, &options).map_err(|parse_errors| {
+        let parse_error = &parse_errors[0];
+
+        match parse_error.position() {
+            Some((line, col)) => format!(
+                "{} at {}:{}",
+                diagnostic::get_message(parse_error),
+                line + 1,
+                col + 1
+            ),
+            None => diagnostic::get_message(parse_error),
+        }
+    })?;
+
+    match json_to_toml(value)? {
+        Value::Table(table) => Ok(table),
+        _ => Err("expected an object".to_string()),
+    }
+}
+
+fn toml_error(raw: &str, err: toml::de::Error) -> String {
+    let message = match err.message().trim() {
+        "" => "invalid TOML".to_string(),
+        message => message.replace('\n', ", "),
+    };
+
+    let start = match err.span() {
+        Some(span) => span.start,
+        None => return message,
+    };
+
+    let line = raw[..start].matches('\n').count();
+    let col = raw[..start]
+        .rfind('\n')
+        .map_or(&raw[..start], |i| &raw[i + 1..start])
+        .chars()
+        .count();
+
+    format!("{} at {}:{}", message, line + 1, col + 1)
+}
+
+impl Config {
+    fn parse(raw: &str, toml: bool, dir: &Path) -> Result<Config, String> {
+        let table = match toml {
+            true => raw.parse::<Table>().map_err(|err| toml_error(raw, err))?,
+            false => parse_json(raw)?,
+        };
+
+        let mut overrides = vec![];
+
+        let settings = get_settings(&table, |key, value| match key {
+            "overrides" => {
+                overrides = value
+                    .as_array()
+                    .ok_or("'overrides' should be a list")?
+                    .iter()
+                    .map(get_override)
+                    .collect::<Result<_, _>>()?;
+                Ok(())
+            }
+            _ => Err(format!("unknown option '{}'", key)),
+        })?;
+
+        Ok(Config {
+            dir: dir.to_path_buf(),
+            settings,
+            overrides,
+        })
+    }
+
+    /// Settings for the file, overrides that come later win
+    fn settings_for(&self, file_path: &Path) -> Settings {
+        let relative = file_path.strip_prefix(&self.dir).unwrap_or(file_path);
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        let file_name = file_path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        let options = MatchOptions {
+            require_literal_separator: true,
+            ..MatchOptions::new()
+        };
+
+        // Patterns without a "/" match the file name in any directory
+        let matches = |pattern: &Pattern| match pattern.as_str().contains('/') {
+            true => pattern.matches_with(&relative, options),
+            false => pattern.matches_with(&file_name, options),
+        };
+
+        self.overrides
+            .iter()
+            .filter(|item| item.files.iter().any(matches))
+            .fold(self.settings.clone(), |settings, item| {
+                item.settings.clone().or(&settings)
+            })
+    }
+}
+
+/// Finds the config that applies to each file, the closest `.joxide.json` or
+/// `joxide.toml` in the directory of the file or above it
+#[derive(Default)]
+pub struct Configs {
+    found: HashMap<PathBuf, Result<Option<Rc<Config>>, String>>,
+}
+
+fn load(dir: &Path) -> Option<Result<Config, String>> {
+    let path = FILE_NAMES
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.is_file())?;
+
+    let config = std::fs::read_to_string(&path)
+        .map_err(|err| err.to_string())
+        .and_then(|raw| Config::parse(&raw, path.ends_with("joxide.toml"), dir))
+        .map_err(|err| format!("Invalid config file '{}', reason: {}", path.display(), err));
+
+    Some(config)
+}
+
+impl Configs {
+    fn find(&mut self, dir: &Path) -> Result<Option<Rc<Config>>, String> {
+        if let Some(found) = self.found.get(dir) {
+            return found.clone();
+        }
+
+        let found = match load(dir) {
+            Some(config) => config.map(|config| Some(Rc::new(config))),
+            None => match dir.parent() {
+                Some(parent) => self.find(parent),
+                None => Ok(None),
+            },
+        };
+
+        self.found.insert(dir.to_path_buf(), found.clone());
+        found
+    }
+
+    /// The directory of the config that applies to `dir` and the settings at
+    /// the top of it, for searching `dir`
+    pub fn dir_settings(&mut self, dir: &Path) -> Result<Option<(PathBuf, Settings)>, String> {
+        let dir = std::path::absolute(dir)
+            .map_err(|err| format!("Unable to find config file, reason: {}", err))?;
+
+        Ok(self
+            .find(&dir)?
+            .map(|config| (config.dir.clone(), config.settings.clone())))
+    }
+
+    /// Settings from the config of the file, empty when there is no config
+    pub fn settings(&mut self, file_path: &Path) -> Result<Settings, String> {
+        let file_path = std::path::absolute(file_path)
+            .map_err(|err| format!("Unable to find config file, reason: {}", err))?;
+
+        let config = match file_path.parent() {
+            Some(dir) => self.find(dir)?,
+            None => None,
+        };
+
+        Ok(match config {
+            Some(config) => config.settings_for(&file_path),
+            None => Settings::default(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let raw = "{\n  // comment\n  \"indent-length\": 2,\n  \"sort-keys\": true,\n  \"dialect\": \"json5\",\n}";
+        let config = Config::parse(raw, false, Path::new("/a")).unwrap();
+
+        assert_eq!(
+            config.settings,
+            Settings {
+                indent_length: Some(2),
+                sort_keys: Some(true),
+                dialect: Some(DialectArg::Json5),
+                ..Settings::default()
+            }
+        );
+
+        let raw = "indent-style = \"tab\"\nline-ending = \"crlf\"\nmax-width = 80\n";
+        let config = Config::parse(raw, true, Path::new("/a")).unwrap();
+
+        assert_eq!(
+            config.settings,
+            Settings {
+                indent_style: Some(IndentStyleArg::Tab),
+                line_ending: Some(LineEndingArg::Crlf),
+                max_width: Some(80),
+                ..Settings::default()
+            }
+        );
+
+        let raw = "exclude = \"dist\"\next = [\"json\", \"map\"]\nhidden = true\n";
+        let config = Config::parse(raw, true, Path::new("/a")).unwrap();
+
+        assert_eq!(
+            config.settings,
+            Settings {
+                exclude: Some(vec!["dist".to_string()]),
+                ext: Some(vec!["json".to_string(), "map".to_string()]),
+                hidden: Some(true),
+                ..Settings::default()
+            }
+        );
+
+        let cases = [
+            (
+                "{\"indent-length\": -1}",
+                "'indent-length' should be a non-negative integer",
+            ),
+            (
+                "{\"dialect\": \"yaml\"}",
+                "'dialect' expected json, jsonc or json5",
+            ),
+            ("{\"tabs\": true}", "unknown option 'tabs'"),
+            (
+                "{\"overrides\": [{\"indent-length\": 2}]}",
+                "every override should have 'files'",
+            ),
+            (
+                "{\"overrides\": [{\"files\": 1}]}",
+                "'files' should be a glob pattern or a list of them",
+            ),
+            (
+                "{\"overrides\": [{\"files\": \"*.json\", \"hidden\": true}]}",
+                "'exclude', 'ext' and 'hidden' can't be in an override",
+            ),
+            (
+                "{\"exclude\": \"[\"}",
+                "invalid pattern '[', Pattern syntax error near position 0: invalid range pattern",
+            ),
+            (
+                "{\"ext\": [1]}",
+                "'ext' should be an extension or a list of them",
+            ),
+            ("[]", "expected an object"),
+            ("{\"a\" 1}", "Missing a ':' separator at 1:6"),
+        ];
+
+        for (raw, expected) in cases {
+            let err = Config::parse(raw, false, Path::new("/a")).err().unwrap();
+            assert_eq!(err, expected, "{}", raw);
+        }
+
+        let toml_cases = [
+            ("sort-keys = true\nindent-length = ", "invalid TOML at 2:17"),
+            (
+                "indent-length = 2\nindent = abc",
+                "invalid string, expected `\"`, `'` at 2:10",
+            ),
+        ];
+
+        for (raw, expected) in toml_cases {
+            let err = Config::parse(raw, true, Path::new("/a")).err().unwrap();
+            assert_eq!(err, expected, "{}", raw);
+        }
+    }
+
+    #[test]
+    fn test_settings_for() {
+        let raw = [
+            "indent-length = 4\n",
+            "[[overrides]]\nfiles = \"package.json\"\nindent-length = 2\n",
+            "[[overrides]]\nfiles = [\"nested/*.json\"]\nsort-keys = true\n",
+            "[[overrides]]\nfiles = \"nested/package.json\"\nindent-length = 3\n",
+        ];
+        let config = Config::parse(&raw.concat(), true, Path::new("/a")).unwrap();
+
+        let settings = |path: &str| {
+            let settings = config.settings_for(Path::new(path));
+            (settings.indent_length, settings.sort_keys)
+        };
+
+        assert_eq!(settings("/a/data.json"), (Some(4), None));
+        assert_eq!(settings("/a/package.json"), (Some(2), None));
+        assert_eq!(settings("/a/b/package.json"), (Some(2), None));
+        assert_eq!(settings("/a/nested/data.json"), (Some(4), Some(true)));
+        assert_eq!(settings("/a/nested/b/data.json"), (Some(4), None));
+        assert_eq!(settings("/a/nested/package.json"), (Some(3), Some(true)));
+    }
+
+    #[test]
+    fn test_or() {
+        let cli = Settings {
+            indent_length: Some(8),
+            ..Settings::default()
+        };
+        let config = Settings {
+            indent_length: Some(2),
+            sort_keys: Some(true),
+            ..Settings::default()
+        };
+
+        assert_eq!(
+            cli.or(&config),
+            Settings {
+                indent_length: Some(8),
+                sort_keys: Some(true),
+                ..Settings::default()
+            }
+        );
+
+        let config = Settings {
+            indent: Some(IndentArg::Auto),
+            ..Settings::default()
+        };
+        let cli = |indent_style| Settings {
+            indent_style,
+            ..Settings::default()
+        };
+
+        assert_eq!(cli(None).or(&config).indent, Some(IndentArg::Auto));
+        assert_eq!(cli(Some(IndentStyleArg::Tab)).or(&config).indent, None);
+    }
+}
@@ -0,0 +1,302 @@
+use crate::lexer::{Token, TokenType};
+use parser::Json;
+use std::borrow::Cow;
+
+#[derive(Debug, PartialEq)]
+pub enum Cst<'a> {
+    Scalar(Json<'a>),
+    /// The decoded string and its source text, quotes included
+    String(Cow<'a, str>, &'a str),
+    Object(Vec<Node<'a>>, Vec<&'a str>),
+    Array(Vec<Node<'a>>, Vec<&'a str>),
+}
+
+/// A value together with the comments attached to it. `leading` comments are
+/// on the lines before the value, `trailing` ones follow it on the same line.
+/// Comments before the closing bracket of a container that can't be attached
+/// to any member are kept as dangling comments of the container.
+#[derive(Debug, PartialEq)]
+pub struct Node<'a> {
+    pub key: Option<Cow<'a, str>>,
+    /// The source text of the key, quotes included
+    pub raw_key: Option<&'a str>,
+    pub value: Cst<'a>,
+    pub leading: Vec<&'a str>,
+    pub trailing: Vec<&'a str>,
+}
+
+struct Builder<'a> {
+    source: &'a str,
+    tokens: &'a [Token<'a>],
+    i: usize,
+    // Comments on the lines after a value, before its comma
+    pending: Vec<&'a str>,
+}
+
+impl<'a> Builder<'a> {
+    fn raw(&self) -> &'a str {
+        let token = &self.tokens[self.i];
+
+        &self.source[token.offset..token.end(self.source)]
+    }
+
+    fn peek(&self) -> Option<&'a TokenType<'a>> {
+        self.tokens.get(self.i).map(|token| &token.token_type)
+    }
+
+    fn comments(&mut self) -> Vec<&'a Token<'a>> {
+        let mut comments = vec![];
+
+        while let Some(TokenType::Comment(_)) = self.peek() {
+            comments.push(&self.tokens[self.i]);
+            self.i += 1;
+        }
+
+        comments
+    }
+
+    // Comments on the line of the previous token and the ones after it
+    fn split_comments(&mut self) -> (Vec<&'a str>, Vec<&'a str>) {
+        let line = self.tokens[self.i - 1].line;
+        let (same_line, next_lines): (Vec<_>, Vec<_>) = self
+            .comments()
+            .into_iter()
+            .partition(|comment| comment.line == line);
+
+        (text(&same_line), text(&next_lines))
+    }
+
+    fn leading(&mut self, nodes: &mut [Node<'a>]) -> Vec<&'a str> {
+        let (same_line, next_lines) = self.split_comments();
+        let mut leading = std::mem::take(&mut self.pending);
+
+        match nodes.last_mut() {
+            Some(previous) if leading.is_empty() => previous.trailing.extend(same_line),
+            _ => leading.extend(same_line),
+        }
+
+        leading.extend(next_lines);
+        leading
+    }
+
+    fn node(
+        &mut self,
+        key: Option<Cow<'a, str>>,
+        json: Json<'a>,
+        leading: Vec<&'a str>,
+    ) -> Node<'a> {
+        let mut trailing = vec![];
+        let raw_key = key.as_ref().map(|_| self.raw());
+
+        if key.is_some() {
+            self.i += 1;
+            trailing.extend(text(&self.comments()));
+            self.i += 1;
+            trailing.extend(text(&self.comments()));
+        }
+
+        let value = self.value(json);
+        let (same_line, next_lines) = self.split_comments();
+
+        trailing.extend(same_line);
+        self.pending.extend(next_lines);
+
+        if let Some(TokenType::Comma) = self.peek() {
+            self.i += 1;
+        }
+
+        Node {
+            key,
+            raw_key,
+            value,
+            leading,
+            trailing,
+        }
+    }
+
+    fn container<I>(&mut self, items: I) -> (Vec<Node<'a>>, Vec<&'a str>)
+    where
+        I: Iterator<Item = (Option<Cow<'a, str>>, Json<'a>)>,
+    {
+        let mut nodes = vec![];
+        self.i += 1;
+
+        for (key, json) in items {
+            let leading = self.leading(&mut nodes);
+            let node = self.node(key, json, leading);
+
+            nodes.push(node);
+        }
+
+        let dangling = self.leading(&mut nodes);
+        self.i += 1;
+
+        (nodes, dangling)
+    }
+
+    fn value(&mut self, json: Json<'a>) -> Cst<'a> {
+        match json {
+            Json::Object(object) => {
+                let items = object.into_iter().map(|(key, value)| (Some(key), value));
+                let (nodes, dangling) = self.container(items);
+
+                Cst::Object(nodes, dangling)
+            }
+            Json::Array(array) => {
+                let (nodes, dangling) =
+                    self.container(array.into_iter().map(|value| (None, value)));
+
+                Cst::Array(nodes, dangling)
+            }
+            Json::String(decoded) => {
+                let raw = self.raw();
+                self.i += 1;
+                Cst::String(decoded, raw)
+            }
+            scalar => {
+                self.i += 1;
+                Cst::Scalar(scalar)
+            }
+        }
+    }
+}
+
+fn text<'a>(comments: &[&'a Token<'a>]) -> Vec<&'a str> {
+    comments
+        .iter()
+        .filter_map(|comment| match comment.token_type {
+            TokenType::Comment(text) => Some(text),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Attaches the comments in `tokens` to `value`, which must be the result of
+/// successfully parsing the same tokens, lexed from `source`.
+pub fn build<'a>(source: &'a str, tokens: &'a [Token<'a>], value: Json<'a>) -> Node<'a> {
+    let mut builder = Builder {
+        source,
+        tokens,
+        i: 0,
+        pending: vec![],
+    };
+
+    let leading = text(&builder.comments());
+    let value = builder.value(value);
+    let trailing = text(&builder.comments());
+
+    Node {
+        key: None,
+        raw_key: None,
+        value,
+        leading,
+        trailing,
+    }
+}
+
+/// Like `build` for the values of a JSON Lines file, comments on the line of a
+/// value trail it and the ones on the lines after it lead the next one.
+pub fn build_lines<'a>(
+    source: &'a str,
+    tokens: &'a [Token<'a>],
+    values: Vec<Json<'a>>,
+) -> Vec<Node<'a>> {
+    let mut builder = Builder {
+        source,
+        tokens,
+        i: 0,
+        pending: vec![],
+    };
+
+    let mut nodes: Vec<Node> = values
+        .into_iter()
+        .map(|value| {
+            let mut leading = std::mem::take(&mut builder.pending);
+            leading.extend(text(&builder.comments()));
+
+            let value = builder.value(value);
+            let (trailing, next_lines) = builder.split_comments();
+            builder.pending = next_lines;
+
+            Node {
+                key: None,
+                raw_key: None,
+                value,
+                leading,
+                trailing,
+            }
+        })
+        .collect();
+
+    if let Some(last) = nodes.last_mut() {
+        last.trailing.append(&mut builder.pending);
+    }
+
+    nodes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lexer::{lex_with, Dialect};
+    use number::Number;
+    use parser::{parse_with, ParseOptions};
+
+    #[test]
+    fn test_build() {
+        let raw = "// head\n{\n  // about a\n  \"a\": 1, // one\n  \"b\": [true /* yes */, \"\\u00e9\"],\n  // left over\n}\n// tail";
+        let tokens = lex_with(raw, Dialect::Jsonc);
+        let options = ParseOptions {
+            dialect: Dialect::Jsonc,
+            ..ParseOptions::default()
+        };
+
+        let value = parse_with(&tokens, &options).unwrap();
+
+        let expected = Node {
+            key: None,
+            raw_key: None,
+            value: Cst::Object(
+                vec![
+                    Node {
+                        key: Some("a".into()),
+                        raw_key: Some("\"a\""),
+                        value: Cst::Scalar(Json::Number(Number::new("1"))),
+                        leading: vec!["// about a"],
+                        trailing: vec!["// one"],
+                    },
+                    Node {
+                        key: Some("b".into()),
+                        raw_key: Some("\"b\""),
+                        value: Cst::Array(
+                            vec![
+                                Node {
+                                    key: None,
+                                    raw_key: None,
+                                    value: Cst::Scalar(Json::Bool(true)),
+                                    leading: vec![],
+                                    trailing: vec!["/* yes */"],
+                                },
+                                Node {
+                                    key: None,
+                                    raw_key: None,
+                                    value: Cst::String("\u{e9}".into(), "\"\\u00e9\""),
+                                    leading: vec![],
+                                    trailing: vec![],
+                                },
+                            ],
+                            vec![],
+                        ),
+                        leading: vec![],
+                        trailing: vec![],
+                    },
+                ],
+                vec!["// left over"],
+            ),
+            leading: vec!["// head"],
+            trailing: vec!["// tail"],
+        };
+
+        assert_eq!(build(raw, &tokens, value), expected);
+    }
+}
@@ -1,6 +1,6 @@
 use crate::{
-    lexer::TokenType,
-    parser::{ParseError, ParseErrorType},
+    lexer::{self, Token, TokenType},
+    parser::{ParseError, ParseErrorType, Suggestion, SuggestionType},
 };
 
 fn get_message_unexpected_token<'a>(parse_error: &'a ParseError<'a>) -> String {
@@ -8,20 +8,59 @@ fn get_message_unexpected_token<'a>(parse_error: &'a ParseError<'a>) -> String {
         None => "Did not expect this word or character".to_string(),
         Some(token) => match parse_error.expected {
             None => format!("Did not expect '{}'", token.token_type),
-            Some(expected) => match expected {
-                TokenType::CloseCurly | TokenType::CloseSquare => format!(
-                    "Did not expect '{}', expected '{}'. Forgot a comma maybe?",
-                    token.token_type, expected
-                ),
-                _ => format!(
-                    "Did not expect '{}', expected '{}'",
-                    token.token_type, expected
-                ),
-            },
+            Some(expected) => format!(
+                "Did not expect '{}', expected '{}'",
+                token.token_type, expected
+            ),
         },
     }
 }
 
+fn get_message_invalid_number<'a>(parse_error: &'a ParseError<'a>) -> String {
+    match parse_error.token.map(|token| &token.token_type) {
+        Some(TokenType::InvalidNumber(symbol, number_error)) => {
+            format!("Invalid number '{}', {}", symbol, number_error)
+        }
+        _ => "Invalid number".to_string(),
+    }
+}
+
+fn get_message_invalid_string<'a>(parse_error: &'a ParseError<'a>) -> String {
+    match parse_error.token.map(|token| &token.token_type) {
+        Some(token_type @ TokenType::InvalidString(_, string_error)) => {
+            format!("Invalid string, found '{}', {}", token_type, string_error)
+        }
+        _ => "Invalid string".to_string(),
+    }
+}
+
+fn get_message_unterminated_string<'a>(parse_error: &'a ParseError<'a>) -> String {
+    let (token, s, file_end) = match parse_error.token {
+        Some(
+            token @ Token {
+                token_type: TokenType::UnterminatedString(s, file_end),
+                ..
+            },
+        ) => (token, *s, *file_end),
+        _ => return "Unterminated string".to_string(),
+    };
+
+    let (line, col) = lexer::position_after(token.line, token.col, s);
+
+    let end = match file_end {
+        true => "the end of the file",
+        false => "the end of the line",
+    };
+
+    format!(
+        "Unterminated string, expected a closing '{}' at {}:{}, {}",
+        s.chars().next().unwrap_or('"'),
+        line + 1,
+        col + 1,
+        end
+    )
+}
+
 pub fn get_message<'a>(parse_error: &'a ParseError<'a>) -> String {
     match parse_error.error_type {
         ParseErrorType::UnexpectedEnd => "File ended unexpectedly".to_string(),
@@ -32,5 +71,43 @@ pub fn get_message<'a>(parse_error: &'a ParseError<'a>) -> String {
         ParseErrorType::DuplicateKey => "Duplicate keys are not valid".to_string(),
         ParseErrorType::KeyNotInQuotes => "Key should be in quotes".to_string(),
         ParseErrorType::MissingColon => "Missing a ':' separator".to_string(),
+        ParseErrorType::InvalidNumber => get_message_invalid_number(parse_error),
+        ParseErrorType::InvalidString => get_message_invalid_string(parse_error),
+        ParseErrorType::UnterminatedString => get_message_unterminated_string(parse_error),
+        ParseErrorType::TrailingContent => match parse_error.token {
+            Some(token) => format!(
+                "Did not expect '{}' after the end of the value",
+                token.token_type
+            ),
+            None => "Did not expect anything after the end of the value".to_string(),
+        },
+    }
+}
+
+/// The note shown next to the related token of an error, if it has one.
+pub fn get_related_label(parse_error: &ParseError) -> Option<&'static str> {
+    let related = parse_error.related?;
+
+    match parse_error.error_type {
+        ParseErrorType::DuplicateKey => Some("first defined here"),
+        ParseErrorType::MissingCloseCurly => Some("the object starts here"),
+        ParseErrorType::MissingCloseSquare => Some("the array starts here"),
+        ParseErrorType::UnexpectedEnd => match related.token_type {
+            TokenType::OpenCurly => Some("the object starts here"),
+            TokenType::OpenSquare => Some("the array starts here"),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+pub fn get_suggestion_message(suggestion: &Suggestion) -> &'static str {
+    match suggestion.suggestion_type {
+        SuggestionType::InsertComma => "insert a comma",
+        SuggestionType::RemoveTrailingComma => "remove the trailing comma",
+        SuggestionType::QuoteKey => "put the key in quotes",
+        SuggestionType::InsertColon => "insert a ':' after the key",
+        SuggestionType::InsertCloseCurly => "close the object with '}'",
+        SuggestionType::InsertCloseSquare => "close the array with ']'",
     }
 }
@@ -0,0 +1,297 @@
+use std::path::Path;
+
+use color::{paint, BOLD, CYAN, GREEN, RED};
+
+const CONTEXT: usize = 3;
+const MAX_TRACE: usize = 10_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Edit {
+    Same,
+    Removed,
+    Added,
+}
+
+fn replace(old: &[&str], new: &[&str]) -> Vec<Edit> {
+    let mut edits = vec![Edit::Removed; old.len()];
+    edits.extend(vec![Edit::Added; new.len()]);
+    edits
+}
+
+// Myers' algorithm, falls back to replacing the whole block when the files are
+// so different that remembering every step would take too much memory
+fn shortest_edits(old: &[&str], new: &[&str]) -> Vec<Edit> {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let max = n + m;
+    let index = |k: isize| (max + k) as usize;
+
+    let mut v = vec![0; 2 * max as usize + 2];
+    let mut trace = vec![];
+
+    'search: for d in 0..=max {
+        if trace.len() * v.len() > MAX_TRACE {
+            return replace(old, new);
+        }
+
+        trace.push(v.clone());
+
+        for k in (-d..=d).step_by(2) {
+            let mut x = match k == -d || (k != d && v[index(k - 1)] < v[index(k + 1)]) {
+                true => v[index(k + 1)],
+                false => v[index(k - 1)] + 1,
+            };
+            let mut y = x - k;
+
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+
+            v[index(k)] = x;
+
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+    }
+
+    let mut edits = vec![];
+    let (mut x, mut y) = (n, m);
+
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let k = x - y;
+
+        let previous_k = match k == -d || (k != d && v[index(k - 1)] < v[index(k + 1)]) {
+            true => k + 1,
+            false => k - 1,
+        };
+        let previous_x = v[index(previous_k)];
+        let previous_y = previous_x - previous_k;
+
+        while x > previous_x && y > previous_y {
+            edits.push(Edit::Same);
+            x -= 1;
+            y -= 1;
+        }
+
+        if d > 0 {
+            edits.push(match x == previous_x {
+                true => Edit::Added,
+                false => Edit::Removed,
+            });
+        }
+
+        x = previous_x;
+        y = previous_y;
+    }
+
+    edits.reverse();
+    edits
+}
+
+fn diff_lines(old: &[&str], new: &[&str]) -> Vec<Edit> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut edits = vec![Edit::Same; prefix];
+    edits.extend(shortest_edits(
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+    ));
+    edits.extend(vec![Edit::Same; suffix]);
+    edits
+}
+
+fn hunk_range(start: usize, count: usize) -> String {
+    match count {
+        0 => format!("{},0", start),
+        _ => format!("{},{}", start + 1, count),
+    }
+}
+
+// `show_cr` writes the carriage return of changed lines as "\r" so that a change
+// of line endings doesn't look like the same line removed and added again
+fn push_line(result: &mut String, prefix: &str, line: &str, style: Option<&str>, show_cr: bool) {
+    let content = line.trim_end_matches('\n');
+    let text = match show_cr && prefix != " " {
+        true => format!("{}{}", prefix, content.replace('\r', "\\r")),
+        false => format!("{}{}", prefix, content.trim_end_matches('\r')),
+    };
+
+    *result += &match style {
+        Some(style) => paint(true, style, &text),
+        None => text,
+    };
+    *result += "\n";
+
+    if !line.ends_with('\n') {
+        *result += "\\ No newline at end of file\n";
+    }
+}
+
+pub fn unified_diff(old: &str, new: &str, file_path: &Path, color: bool) -> String {
+    let old_lines: Vec<&str> = old.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new.split_inclusive('\n').collect();
+
+    let mut endings = old_lines
+        .iter()
+        .chain(&new_lines)
+        .filter(|line| line.ends_with('\n'));
+    let show_cr = endings.clone().any(|line| line.ends_with("\r\n"))
+        && endings.any(|line| !line.ends_with("\r\n"));
+
+    // Every edit along with the line numbers in the old and new file it applies to
+    let mut ops = vec![];
+    let (mut i, mut j) = (0, 0);
+
+    for edit in diff_lines(&old_lines, &new_lines) {
+        ops.push((edit, i, j));
+
+        match edit {
+            Edit::Same => {
+                i += 1;
+                j += 1;
+            }
+            Edit::Removed => i += 1,
+            Edit::Added => j += 1,
+        }
+    }
+
+    let header = format!("--- {}\n+++ {}", file_path.display(), file_path.display());
+    let mut result = paint(color, BOLD, &header) + "\n";
+
+    let is_change = |op: &(Edit, usize, usize)| op.0 != Edit::Same;
+    let mut next = 0;
+
+    while let Some(start) = ops[next..].iter().position(is_change).map(|p| p + next) {
+        let mut end = start;
+
+        loop {
+            end += ops[end..].iter().take_while(|op| is_change(op)).count();
+
+            match ops[end..].iter().position(is_change) {
+                Some(gap) if gap <= 2 * CONTEXT => end += gap,
+                _ => break,
+            }
+        }
+
+        next = (end + CONTEXT).min(ops.len());
+
+        let hunk = &ops[start.saturating_sub(CONTEXT)..next];
+        let (_, old_start, new_start) = hunk[0];
+        let old_count = hunk.iter().filter(|op| op.0 != Edit::Added).count();
+        let new_count = hunk.iter().filter(|op| op.0 != Edit::Removed).count();
+
+        let range = format!(
+            "@@ -{} +{} @@",
+            hunk_range(old_start, old_count),
+            hunk_range(new_start, new_count)
+        );
+        result += &(paint(color, CYAN, &range) + "\n");
+
+        for &(edit, i, j) in hunk {
+            let (prefix, line, style) = match edit {
+                Edit::Same => (" ", old_lines[i], None),
+                Edit::Removed => ("-", old_lines[i], color.then_some(RED)),
+                Edit::Added => ("+", new_lines[j], color.then_some(GREEN)),
+            };
+
+            push_line(&mut result, prefix, line, style, show_cr);
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_lines() {
+        let old = ["{", "\"a\": 1,", "\"b\": 2", "}"];
+        let new = ["{", "    \"a\": 1,", "    \"b\": 2", "}"];
+
+        assert_eq!(
+            diff_lines(&old, &new),
+            vec![
+                Edit::Same,
+                Edit::Removed,
+                Edit::Removed,
+                Edit::Added,
+                Edit::Added,
+                Edit::Same,
+            ]
+        );
+
+        let old = ["a", "b", "c", "a", "b", "b", "a"];
+        let new = ["c", "b", "a", "b", "a", "c"];
+        let edits = diff_lines(&old, &new);
+
+        assert_eq!(edits.iter().filter(|edit| **edit != Edit::Same).count(), 5);
+        assert_eq!(diff_lines(&[], &["a"]), vec![Edit::Added]);
+    }
+
+    #[test]
+    fn test_unified_diff() {
+        let old = "{\n\"a\": 1,\n\"b\": 2,\n\"c\": 3,\n\"d\": 4,\n\"e\": 5,\n\"f\": 6,\n\"g\": 7,\n\"h\": 8,\n\"i\": 9}";
+        let new = old.replace("\"a\": 1", "\"a\": 0").replace("9}", "9\n}\n");
+
+        let diff = unified_diff(old, &new, Path::new("test.json"), true);
+        let expected = [
+            "\x1b[1m--- test.json\n+++ test.json\x1b[0m\n",
+            "\x1b[36m@@ -1,5 +1,5 @@\x1b[0m\n",
+            " {\n",
+            "\x1b[31m-\"a\": 1,\x1b[0m\n",
+            "\x1b[32m+\"a\": 0,\x1b[0m\n",
+            " \"b\": 2,\n \"c\": 3,\n \"d\": 4,\n",
+            "\x1b[36m@@ -7,4 +7,5 @@\x1b[0m\n",
+            " \"f\": 6,\n \"g\": 7,\n \"h\": 8,\n",
+            "\x1b[31m-\"i\": 9}\x1b[0m\n\\ No newline at end of file\n",
+            "\x1b[32m+\"i\": 9\x1b[0m\n",
+            "\x1b[32m+}\x1b[0m\n",
+        ];
+
+        assert_eq!(diff, expected.concat());
+
+        let diff = unified_diff("[1]", "[2]\n", Path::new("test.json"), false);
+        let expected = [
+            "--- test.json\n+++ test.json\n",
+            "@@ -1,1 +1,1 @@\n",
+            "-[1]\n\\ No newline at end of file\n",
+            "+[2]\n",
+        ];
+
+        assert_eq!(diff, expected.concat());
+
+        let diff = unified_diff(
+            "[\r\n1\r\n]\r\n",
+            "[\n1\n]\n",
+            Path::new("test.json"),
+            false,
+        );
+        let expected = [
+            "--- test.json\n+++ test.json\n",
+            "@@ -1,3 +1,3 @@\n",
+            "-[\\r\n-1\\r\n-]\\r\n",
+            "+[\n+1\n+]\n",
+        ];
+
+        assert_eq!(diff, expected.concat());
+
+        let diff = unified_diff(
+            "[\r\n1\r\n]\r\n",
+            "[\r\n2\r\n]\r\n",
+            Path::new("test.json"),
+            false,
+        );
+
+        assert!(!diff.contains("\\r"));
+    }
+}
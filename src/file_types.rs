@@ -0,0 +1,115 @@
+use std::path::Path;
+
+use crate::args::DialectArg;
+use config::Settings;
+
+// Extensions searched for in directories and the dialect they are written in
+const EXTENSIONS: [(&str, DialectArg); 11] = [
+    ("json", DialectArg::Json),
+    ("jsonc", DialectArg::Jsonc),
+    ("json5", DialectArg::Json5),
+    ("jsonl", DialectArg::Json),
+    ("ndjson", DialectArg::Json),
+    ("geojson", DialectArg::Json),
+    ("topojson", DialectArg::Json),
+    ("jsonld", DialectArg::Json),
+    ("har", DialectArg::Json),
+    ("webmanifest", DialectArg::Json),
+    ("code-workspace", DialectArg::Jsonc),
+];
+
+// Extensions of JSON Lines files
+const JSON_LINES: [&str; 2] = ["jsonl", "ndjson"];
+
+// Files searched for whatever their extension is, these win over the extension
+const FILE_NAMES: [(&str, DialectArg); 8] = [
+    (".babelrc", DialectArg::Json5),
+    (".jshintrc", DialectArg::Jsonc),
+    (".jscsrc", DialectArg::Jsonc),
+    (".swcrc", DialectArg::Json),
+    (".watchmanconfig", DialectArg::Json),
+    ("tsconfig.json", DialectArg::Jsonc),
+    ("jsconfig.json", DialectArg::Jsonc),
+    ("composer.lock", DialectArg::Json),
+];
+
+fn get_name(path: &Path) -> Option<&str> {
+    path.file_name()?.to_str()
+}
+
+fn get_extension(path: &Path) -> Option<&str> {
+    path.extension()?.to_str()
+}
+
+/// Whether a file found in a directory should be read, `extensions` given
+/// with --ext replace the built-in extensions and file names
+pub fn is_included(path: &Path, extensions: &[String]) -> bool {
+    let extension = get_extension(path);
+
+    if !extensions.is_empty() {
+        return extensions
+            .iter()
+            .any(|ext| Some(ext.trim_start_matches('.')) == extension);
+    }
+
+    let name = get_name(path);
+
+    FILE_NAMES
+        .iter()
+        .any(|(file_name, _)| Some(*file_name) == name)
+        || EXTENSIONS.iter().any(|(ext, _)| Some(*ext) == extension)
+}
+
+/// The dialect and JSON Lines mode picked by the name of the file
+pub fn get_settings(path: &Path) -> Settings {
+    let (name, extension) = (get_name(path), get_extension(path));
+
+    let by_name = FILE_NAMES
+        .iter()
+        .find(|(file_name, _)| Some(*file_name) == name);
+    let by_extension = EXTENSIONS.iter().find(|(ext, _)| Some(*ext) == extension);
+
+    Settings {
+        dialect: by_name.or(by_extension).map(|(_, dialect)| *dialect),
+        json_lines: extension
+            .is_some_and(|ext| JSON_LINES.contains(&ext))
+            .then_some(true),
+        ..Settings::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_included() {
+        assert!(is_included(Path::new("a/b.json"), &[]));
+        assert!(is_included(Path::new("a/b.geojson"), &[]));
+        assert!(is_included(Path::new("a/.babelrc"), &[]));
+        assert!(!is_included(Path::new("a/b.txt"), &[]));
+        assert!(!is_included(Path::new("a/json"), &[]));
+
+        let extensions = ["json5".to_string(), ".txt".to_string()];
+
+        assert!(is_included(Path::new("a/b.txt"), &extensions));
+        assert!(is_included(Path::new("a/b.json5"), &extensions));
+        assert!(!is_included(Path::new("a/b.json"), &extensions));
+        assert!(!is_included(Path::new("a/.babelrc"), &extensions));
+    }
+
+    #[test]
+    fn test_get_settings() {
+        let settings = |path: &str| {
+            let settings = get_settings(Path::new(path));
+            (settings.dialect, settings.json_lines)
+        };
+
+        assert_eq!(settings("a.json"), (Some(DialectArg::Json), None));
+        assert_eq!(settings("a/b.jsonc"), (Some(DialectArg::Jsonc), None));
+        assert_eq!(settings("a.jsonl"), (Some(DialectArg::Json), Some(true)));
+        assert_eq!(settings(".babelrc"), (Some(DialectArg::Json5), None));
+        assert_eq!(settings("tsconfig.json"), (Some(DialectArg::Jsonc), None));
+        assert_eq!(settings("<stdin>"), (None, None));
+    }
+}
@@ -1,34 +1,248 @@
-use crate::lexer::TokenType;
+use crate::cst::{Cst, Node};
+use crate::lexer::{is_json_string, TokenType};
 use parser::Json;
-use std::{collections::BTreeMap, usize};
+use std::borrow::Cow;
+use unicode_width::UnicodeWidthStr;
 
-fn comma_builder<I, T, F>(
-    length: usize,
-    iterator: I,
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LineEnding {
+    Lf,
+    Crlf,
+    Cr,
+}
+
+impl LineEnding {
+    pub fn detect(s: &str) -> LineEnding {
+        match s.find(['\n', '\r']).map(|i| &s[i..]) {
+            Some(rest) if rest.starts_with("\r\n") => LineEnding::Crlf,
+            Some(rest) if rest.starts_with('\r') => LineEnding::Cr,
+            _ => LineEnding::Lf,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::Crlf => "\r\n",
+            LineEnding::Cr => "\r",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum IndentStyle {
+    #[default]
+    Space,
+    Tab,
+}
+
+/// The indent of the least indented line in `s`, `None` when no line is
+/// indented. A file indented with tabs uses one tab per level.
+pub fn detect_indent(s: &str) -> Option<(IndentStyle, usize)> {
+    let indents = s.split(['\n', '\r']).skip(1).filter_map(|line| {
+        let content = line.trim_start_matches([' ', '\t']);
+        let indent = &line[..line.len() - content.len()];
+
+        match (indent.is_empty(), content.is_empty()) {
+            (false, false) => Some(indent),
+            _ => None,
+        }
+    });
+
+    indents
+        .map(|indent| match indent.starts_with('\t') {
+            true => (IndentStyle::Tab, 1),
+            false => (
+                IndentStyle::Space,
+                indent.len() - indent.trim_start_matches(' ').len(),
+            ),
+        })
+        .min_by_key(|(_, length)| *length)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormatOptions {
+    pub indent_length: usize,
+    pub indent_style: IndentStyle,
+    pub sort_keys: bool,
+    pub line_ending: LineEnding,
+    /// Containers that fit within this many columns are kept on one line,
+    /// 0 puts every element on its own line
+    pub max_width: usize,
+}
+
+impl Default for FormatOptions {
+    fn default() -> FormatOptions {
+        FormatOptions {
+            indent_length: 4,
+            indent_style: IndentStyle::Space,
+            sort_keys: false,
+            line_ending: LineEnding::Lf,
+            max_width: 0,
+        }
+    }
+}
+
+impl FormatOptions {
+    // With tabs every level is a single tab, whatever the indent length
+    fn indent(&self, ilvl: usize) -> String {
+        match self.indent_style {
+            IndentStyle::Space => " ".repeat(ilvl * self.indent_length),
+            IndentStyle::Tab => "\t".repeat(ilvl),
+        }
+    }
+
+    // The columns left on a line indented `ilvl` times, with room for `suffix`
+    // characters after the value. Tabs count as `indent_length` columns.
+    fn budget(&self, ilvl: usize, suffix: usize) -> Option<usize> {
+        match (self.max_width, self.indent_length) {
+            (0, _) | (_, 0) => None,
+            (max_width, ilen) => max_width.checked_sub(ilvl * ilen + suffix),
+        }
+    }
+}
+
+fn sorted<'a, T>(items: &'a [T], key: impl Fn(&T) -> &str, options: &FormatOptions) -> Vec<&'a T> {
+    let mut items: Vec<&T> = items.iter().collect();
+
+    if options.sort_keys {
+        items.sort_by(|a, b| key(a).cmp(key(b)));
+    }
+
+    items
+}
+
+// `items` on a single line, `None` when they don't fit in `budget` columns
+fn flat_builder<T>(
+    items: &[T],
+    open: TokenType,
+    flat_item: impl Fn(&T, usize) -> Option<String>,
+    budget: usize,
+) -> Option<String> {
+    let mut result = match open {
+        TokenType::OpenCurly => "{".to_string(),
+        _ => "[".to_string(),
+    };
+
+    for (index, item) in items.iter().enumerate() {
+        if index != 0 {
+            result += ", ";
+        }
+
+        result += &flat_item(item, budget.checked_sub(result.width())?)?;
+    }
+
+    result += match open {
+        TokenType::OpenCurly => "}",
+        _ => "]",
+    };
+
+    (result.width() <= budget).then_some(result)
+}
+
+// `key` is already quoted
+fn flat_member(
+    key: Option<String>,
+    budget: usize,
+    flat_value: impl Fn(usize) -> Option<String>,
+) -> Option<String> {
+    match key {
+        Some(key) => {
+            let prefix = format!("{}: ", key);
+            Some(prefix.clone() + &flat_value(budget.checked_sub(prefix.width())?)?)
+        }
+        None => flat_value(budget),
+    }
+}
+
+fn flat(val: &Json, budget: usize, options: &FormatOptions) -> Option<String> {
+    let result = match val {
+        Json::Null => "null".to_string(),
+        Json::Bool(b) => format!("{}", b),
+        Json::Number(n) => format!("{}", n),
+        Json::String(s) => string(s),
+        Json::Object(obj) => {
+            let members = sorted(obj, |(key, _)| key, options);
+            let flat_item = |(key, val): &&(Cow<str>, Json), budget| {
+                flat_member(Some(string(key)), budget, |budget| {
+                    flat(val, budget, options)
+                })
+            };
+
+            flat_builder(&members, TokenType::OpenCurly, flat_item, budget)?
+        }
+        Json::Array(arr) => flat_builder(
+            arr,
+            TokenType::OpenSquare,
+            |val, budget| flat(val, budget, options),
+            budget,
+        )?,
+    };
+
+    (result.width() <= budget).then_some(result)
+}
+
+// Comments may be line comments, so containers with any are never flattened
+fn flat_cst(val: &Cst, budget: usize, options: &FormatOptions) -> Option<String> {
+    let flat_node = |node: &&Node, budget| match node.leading.is_empty() && node.trailing.is_empty()
+    {
+        true => flat_member(node_key(node), budget, |budget| {
+            flat_cst(&node.value, budget, options)
+        }),
+        false => None,
+    };
+
+    match val {
+        Cst::Scalar(val) => flat(val, budget, options),
+        Cst::String(decoded, raw) => {
+            let result = source_string(decoded, raw);
+            (result.width() <= budget).then_some(result)
+        }
+        Cst::Object(nodes, dangling) if dangling.is_empty() => {
+            let nodes = sorted(nodes, |node| node.key.as_deref().unwrap_or(""), options);
+            flat_builder(&nodes, TokenType::OpenCurly, flat_node, budget)
+        }
+        Cst::Array(nodes, dangling) if dangling.is_empty() => {
+            let nodes: Vec<&Node> = nodes.iter().collect();
+            flat_builder(&nodes, TokenType::OpenSquare, flat_node, budget)
+        }
+        _ => None,
+    }
+}
+
+fn comma_builder<T, F, G>(
+    items: Vec<T>,
     open: TokenType,
     formatter: F,
+    flat_item: G,
     ilvl: usize,
-    ilen: usize,
+    options: &FormatOptions,
 ) -> String
 where
-    F: Fn(T, usize, usize) -> String,
-    I: std::iter::Iterator<Item = T>,
+    F: Fn(T, usize, &FormatOptions) -> String,
+    G: Fn(&T, usize) -> Option<String>,
 {
+    let ilen = options.indent_length;
+    let length = items.len();
+
     let mut result = match open {
         TokenType::OpenCurly => "{".to_string(),
         _ => "[".to_string(),
     };
 
-    for (index, item) in iterator.enumerate() {
+    for (index, item) in items.into_iter().enumerate() {
         if ilen != 0 {
-            result += format!(
-                "\n{}{}",
-                " ".repeat((ilvl + 1) * ilen),
-                formatter(item, ilvl + 1, ilen)
-            )
-            .as_str();
+            let suffix = usize::from(index != length - 1);
+            let formatted = match options.budget(ilvl + 1, suffix) {
+                Some(budget) => flat_item(&item, budget),
+                None => None,
+            };
+
+            result += "\n";
+            result += &options.indent(ilvl + 1);
+            result += &formatted.unwrap_or_else(|| formatter(item, ilvl + 1, options));
         } else {
-            result += formatter(item, ilvl, ilen).as_str();
+            result += formatter(item, ilvl, options).as_str();
         }
 
         if index != length - 1 {
@@ -38,7 +252,7 @@ where
 
     if ilen != 0 {
         result += "\n";
-        result += &" ".repeat(ilvl * ilen);
+        result += &options.indent(ilvl);
     }
 
     result += match open {
@@ -49,58 +263,282 @@ where
     result
 }
 
-fn array(arr: Vec<Json>, ilvl: usize, ilen: usize) -> String {
-    comma_builder(
-        arr.len(),
-        arr.into_iter(),
-        TokenType::OpenSquare,
-        value,
-        ilvl,
-        ilen,
-    )
+fn array(arr: Vec<Json>, ilvl: usize, options: &FormatOptions) -> String {
+    let flat_item = |val: &Json, budget| flat(val, budget, options);
+
+    comma_builder(arr, TokenType::OpenSquare, value, flat_item, ilvl, options)
 }
 
-fn object(obj: BTreeMap<&str, Json>, ilvl: usize, ilen: usize) -> String {
-    let formatter = |item: (&str, Json), ilvl: usize, ilen: usize| -> String {
+fn object(mut obj: Vec<(Cow<str>, Json)>, ilvl: usize, options: &FormatOptions) -> String {
+    if options.sort_keys {
+        obj.sort_by(|(a, _), (b, _)| a.cmp(b));
+    }
+
+    let formatter = |item: (Cow<str>, Json), ilvl: usize, options: &FormatOptions| -> String {
         let (key, val) = item;
 
-        if ilen != 0 {
-            format!("\"{}\": {}", key, value(val, ilvl, ilen))
+        if options.indent_length != 0 {
+            format!("{}: {}", string(&key), value(val, ilvl, options))
         } else {
-            format!("\"{}\":{}", key, value(val, ilvl, ilen))
+            format!("{}:{}", string(&key), value(val, ilvl, options))
         }
     };
 
+    let flat_item = |(key, val): &(Cow<str>, Json), budget| {
+        flat_member(Some(string(key)), budget, |budget| {
+            flat(val, budget, options)
+        })
+    };
+
     comma_builder(
-        obj.len(),
-        obj.into_iter(),
+        obj,
         TokenType::OpenCurly,
         formatter,
+        flat_item,
         ilvl,
-        ilen,
+        options,
     )
 }
 
-fn value(val: Json, ilvl: usize, ilen: usize) -> String {
+fn string(s: &str) -> String {
+    let mut result = String::with_capacity(s.len() + 2);
+
+    result.push('"');
+
+    for c in s.chars() {
+        match c {
+            '"' => result += "\\\"",
+            '\\' => result += "\\\\",
+            '\n' => result += "\\n",
+            '\r' => result += "\\r",
+            '\t' => result += "\\t",
+            '\u{8}' => result += "\\b",
+            '\u{c}' => result += "\\f",
+            c if (c as u32) < 0x20 => result += &format!("\\u{:04x}", c as u32),
+            c => result.push(c),
+        }
+    }
+
+    result.push('"');
+    result
+}
+
+// Strings are kept as they are written unless they aren't valid JSON, like
+// JSON5 strings in single quotes
+fn source_string(decoded: &str, raw: &str) -> String {
+    match is_json_string(raw) {
+        true => raw.to_string(),
+        false => string(decoded),
+    }
+}
+
+fn node_key(node: &Node) -> Option<String> {
+    let key = node.key.as_deref()?;
+
+    Some(match node.raw_key {
+        Some(raw) => source_string(key, raw),
+        None => string(key),
+    })
+}
+
+fn value(val: Json, ilvl: usize, options: &FormatOptions) -> String {
     match val {
         Json::Null => "null".to_string(),
         Json::Bool(b) => format!("{}", b),
         Json::Number(n) => format!("{}", n),
-        Json::String(s) => format!("\"{}\"", s),
-        Json::Object(obj) => object(obj, ilvl, ilen),
-        Json::Array(arr) => array(arr, ilvl, ilen),
+        Json::String(s) => string(&s),
+        Json::Object(obj) => object(obj, ilvl, options),
+        Json::Array(arr) => array(arr, ilvl, options),
     }
 }
 
-pub fn format_json(val: Json, indent_length: usize) -> String {
-    format!("{}\n", value(val, 0, indent_length))
+fn comment(text: &str, options: &FormatOptions) -> String {
+    let text = text.replace("\r\n", "\n").replace('\r', "\n");
+
+    // Without line breaks a line comment would swallow everything after it
+    match text.strip_prefix("//") {
+        Some(line) if options.indent_length == 0 => format!("/*{} */", line.replace("*/", "* /")),
+        _ => text,
+    }
+}
+
+fn node_builder(
+    nodes: Vec<Node>,
+    dangling: Vec<&str>,
+    open: TokenType,
+    ilvl: usize,
+    options: &FormatOptions,
+) -> String {
+    let ilen = options.indent_length;
+    let length = nodes.len();
+    let new_line = |ilvl: usize| match ilen {
+        0 => String::new(),
+        _ => format!("\n{}", options.indent(ilvl)),
+    };
+
+    let mut result = match open {
+        TokenType::OpenCurly => "{".to_string(),
+        _ => "[".to_string(),
+    };
+
+    for (index, node) in nodes.into_iter().enumerate() {
+        for text in &node.leading {
+            result += &new_line(ilvl + 1);
+            result += &comment(text, options);
+        }
+
+        let suffix = usize::from(index != length - 1);
+        let flattened = match options.budget(ilvl + 1, suffix) {
+            Some(budget) => flat_member(node_key(&node), budget, |budget| {
+                flat_cst(&node.value, budget, options)
+            }),
+            None => None,
+        };
+
+        result += &new_line(ilvl + 1);
+        result += &match flattened {
+            Some(flattened) => flattened,
+            None => member(node_key(&node), node.value, ilvl + 1, options),
+        };
+
+        if index != length - 1 {
+            result += ",";
+        }
+
+        for (n, text) in node.trailing.iter().enumerate() {
+            // Nothing can follow a line comment on its line, and a comment after
+            // one that spans lines would be on a later line when formatted again
+            let ends_line =
+                |previous: &str| previous.starts_with("//") || previous.contains(['\n', '\r']);
+
+            match n != 0 && ends_line(node.trailing[n - 1]) {
+                true => result += &new_line(ilvl + 1),
+                false if ilen != 0 => result += " ",
+                false => (),
+            }
+
+            result += &comment(text, options);
+        }
+    }
+
+    for text in &dangling {
+        result += &new_line(ilvl + 1);
+        result += &comment(text, options);
+    }
+
+    result += &new_line(ilvl);
+    result += match open {
+        TokenType::OpenCurly => "}",
+        _ => "]",
+    };
+
+    result
+}
+
+// `key` is already quoted
+fn member(key: Option<String>, val: Cst, ilvl: usize, options: &FormatOptions) -> String {
+    let formatted = match val {
+        Cst::Scalar(val) => value(val, ilvl, options),
+        Cst::String(decoded, raw) => source_string(&decoded, raw),
+        Cst::Object(mut nodes, dangling) => {
+            if options.sort_keys {
+                nodes.sort_by(|a, b| a.key.cmp(&b.key));
+            }
+
+            node_builder(nodes, dangling, TokenType::OpenCurly, ilvl, options)
+        }
+        Cst::Array(nodes, dangling) => {
+            node_builder(nodes, dangling, TokenType::OpenSquare, ilvl, options)
+        }
+    };
+
+    match key {
+        Some(key) if options.indent_length != 0 => format!("{}: {}", key, formatted),
+        Some(key) => format!("{}:{}", key, formatted),
+        None => formatted,
+    }
+}
+
+fn with_line_ending(formatted: String, options: &FormatOptions) -> String {
+    match options.line_ending {
+        LineEnding::Lf => formatted,
+        line_ending => formatted.replace('\n', line_ending.as_str()),
+    }
+}
+
+/// Formats a value without its source, so strings are written again from their
+/// decoded text. Use `format_cst` to keep them as they are in the source.
+pub fn format_json(val: Json, options: &FormatOptions) -> String {
+    let formatted = match options.budget(0, 0) {
+        Some(budget) => flat(&val, budget, options),
+        None => None,
+    };
+
+    let formatted = formatted.unwrap_or_else(|| value(val, 0, options));
+
+    with_line_ending(formatted + "\n", options)
+}
+
+pub fn format_cst(node: Node, options: &FormatOptions) -> String {
+    let separator = match options.indent_length {
+        0 => "",
+        _ => "\n",
+    };
+
+    let mut formatted = String::new();
+
+    for text in &node.leading {
+        formatted += &comment(text, options);
+        formatted += separator;
+    }
+
+    let flattened = match options.budget(0, 0) {
+        Some(budget) => flat_cst(&node.value, budget, options),
+        None => None,
+    };
+
+    formatted += &match flattened {
+        Some(flattened) => flattened,
+        None => member(None, node.value, 0, options),
+    };
+
+    for text in &node.trailing {
+        formatted += separator;
+        formatted += &comment(text, options);
+    }
+
+    with_line_ending(formatted + "\n", options)
+}
+
+/// Formats the values of a JSON Lines file built with `cst::build_lines`, each
+/// on a single line
+pub fn format_json_lines(nodes: Vec<Node>, options: &FormatOptions) -> String {
+    let options = FormatOptions {
+        indent_length: 0,
+        ..options.clone()
+    };
+
+    nodes
+        .into_iter()
+        .map(|node| format_cst(node, &options))
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{lexer, parser};
+    use crate::{cst, lexer, parser};
+
+    use super::{
+        detect_indent, format_cst, format_json, format_json_lines, FormatOptions, IndentStyle,
+        LineEnding,
+    };
 
-    use super::format_json;
+    fn indent(indent_length: usize) -> FormatOptions {
+        FormatOptions {
+            indent_length,
+            ..FormatOptions::default()
+        }
+    }
 
     #[test]
     fn test_formatter() {
@@ -109,11 +547,11 @@ mod tests {
         for i in 0..10 {
             let tokens = lexer::lex(raw);
             let value = parser::parse(&tokens).unwrap();
-            let formatted = format_json(value, i);
+            let formatted = format_json(value, &indent(i));
 
             let tokens_rev = lexer::lex(&formatted);
             let value_rev = parser::parse(&tokens_rev).unwrap();
-            let formatted_rev = format_json(value_rev, 0);
+            let formatted_rev = format_json(value_rev, &indent(0));
 
             assert_eq!(formatted_rev, raw);
         }
@@ -134,9 +572,289 @@ mod tests {
 
             let tokens = lexer::lex(raw);
             let value = parser::parse(&tokens).unwrap();
-            let formatted = format_json(value, i);
+            let formatted = format_json(value, &indent(i));
 
             assert_eq!(formatted, expected[i]);
         }
     }
+
+    #[test]
+    fn test_indent_style() {
+        let raw = "{\"a\":[1,{}]}";
+        let tokens = lexer::lex(raw);
+
+        for indent_length in [1, 4] {
+            let options = FormatOptions {
+                indent_length,
+                indent_style: IndentStyle::Tab,
+                ..FormatOptions::default()
+            };
+
+            let value = parser::parse(&tokens).unwrap();
+            let formatted = format_json(value, &options);
+
+            assert_eq!(formatted, "{\n\t\"a\": [\n\t\t1,\n\t\t{\n\t\t}\n\t]\n}\n");
+        }
+
+        let options = FormatOptions {
+            indent_length: 0,
+            indent_style: IndentStyle::Tab,
+            ..FormatOptions::default()
+        };
+
+        let value = parser::parse(&tokens).unwrap();
+        assert_eq!(format_json(value, &options), "{\"a\":[1,{}]}\n");
+    }
+
+    #[test]
+    fn test_detect_indent() {
+        let cases = [
+            (
+                "{\n  \"a\": [\n    1\n  ]\n}",
+                Some((IndentStyle::Space, 2)),
+            ),
+            (
+                "{\r\n\t\"a\": [\r\n\t\t1\r\n\t]\r\n}",
+                Some((IndentStyle::Tab, 1)),
+            ),
+            ("{\n\n   \n    \"a\": 1\n}", Some((IndentStyle::Space, 4))),
+            ("  {\"a\": 1}", None),
+            ("{\n\"a\": 1\n}", None),
+        ];
+
+        for (raw, expected) in cases {
+            assert_eq!(detect_indent(raw), expected, "{:?}", raw);
+        }
+    }
+
+    #[test]
+    fn test_max_width() {
+        let raw = "{\"m\":[[1,0],[0,1]],\"e\":[],\"s\":{\"b\":\"日本\",\"a\":null}}";
+        let tokens = lexer::lex(raw);
+
+        let expected = [
+            (70, "{\"m\": [[1, 0], [0, 1]], \"e\": [], \"s\": {\"b\": \"日本\", \"a\": null}}\n"),
+            (31, "{\n  \"m\": [[1, 0], [0, 1]],\n  \"e\": [],\n  \"s\": {\"b\": \"日本\", \"a\": null}\n}\n"),
+            (30, "{\n  \"m\": [[1, 0], [0, 1]],\n  \"e\": [],\n  \"s\": {\n    \"b\": \"日本\",\n    \"a\": null\n  }\n}\n"),
+            (12, "{\n  \"m\": [\n    [1, 0],\n    [0, 1]\n  ],\n  \"e\": [],\n  \"s\": {\n    \"b\": \"日本\",\n    \"a\": null\n  }\n}\n"),
+        ];
+
+        for (max_width, expected) in expected {
+            let options = FormatOptions {
+                indent_length: 2,
+                max_width,
+                ..FormatOptions::default()
+            };
+
+            let value = parser::parse(&tokens).unwrap();
+            assert_eq!(format_json(value, &options), expected, "{}", max_width);
+
+            let value = parser::parse(&tokens).unwrap();
+            let formatted = format_cst(cst::build(raw, &tokens, value), &options);
+            assert_eq!(formatted, expected, "{}", max_width);
+        }
+
+        let options = FormatOptions {
+            indent_length: 2,
+            sort_keys: true,
+            max_width: 80,
+            ..FormatOptions::default()
+        };
+
+        let value = parser::parse(&tokens).unwrap();
+        assert_eq!(
+            format_json(value, &options),
+            "{\"e\": [], \"m\": [[1, 0], [0, 1]], \"s\": {\"a\": null, \"b\": \"日本\"}}\n"
+        );
+
+        let raw = "[[1, /* one */ 2], [3]]";
+        let tokens = lexer::lex_with(raw, lexer::Dialect::Jsonc);
+        let jsonc = parser::ParseOptions {
+            dialect: lexer::Dialect::Jsonc,
+            ..parser::ParseOptions::default()
+        };
+
+        let value = parser::parse_with(&tokens, &jsonc).unwrap();
+        assert_eq!(
+            format_cst(cst::build(raw, &tokens, value), &options),
+            "[\n  [\n    1, /* one */\n    2\n  ],\n  [3]\n]\n"
+        );
+    }
+
+    #[test]
+    fn test_key_order() {
+        let raw = "{\"b\":1,\"a\":{\"z\":true,\"y\":null},\"c\":[]}";
+
+        let tokens = lexer::lex(raw);
+        let value = parser::parse(&tokens).unwrap();
+        let formatted = format_json(value, &indent(0));
+
+        assert_eq!(formatted, format!("{}\n", raw));
+
+        let options = FormatOptions {
+            indent_length: 0,
+            sort_keys: true,
+            ..FormatOptions::default()
+        };
+
+        let value = parser::parse(&tokens).unwrap();
+        let formatted = format_json(value, &options);
+
+        assert_eq!(
+            formatted,
+            "{\"a\":{\"y\":null,\"z\":true},\"b\":1,\"c\":[]}\n"
+        );
+    }
+
+    #[test]
+    fn test_numbers_unchanged() {
+        let raw = "[12345678901234567890,1.0,1e3,-0.5E-7,0]\n";
+
+        let tokens = lexer::lex(raw);
+        let value = parser::parse(&tokens).unwrap();
+        let formatted = format_json(value, &indent(0));
+
+        assert_eq!(formatted, raw);
+    }
+
+    #[test]
+    fn test_string_escapes() {
+        let raw = "[\"tab\\there\",\"\\u00e9\\/\\ud83d\\ude00\",\"\\\"\\\\\\u0001\"]";
+        let expected = "[\"tab\\there\",\"\u{e9}/\u{1f600}\",\"\\\"\\\\\\u0001\"]\n";
+
+        let tokens = lexer::lex(raw);
+        let value = parser::parse(&tokens).unwrap();
+        let formatted = format_json(value, &indent(0));
+
+        assert_eq!(formatted, expected);
+    }
+
+    #[test]
+    fn test_strings_unchanged() {
+        let raw = "{\"k\\u0041\": [\"\\u00e9\\/\\ud83d\\ude00\", 'it\\'s', \"\\x41\"], key: 1}";
+        let expected =
+            "{\"k\\u0041\": [\"\\u00e9\\/\\ud83d\\ude00\", \"it's\", \"A\"], \"key\": 1}\n";
+
+        let tokens = lexer::lex_with(raw, lexer::Dialect::Json5);
+        let options = parser::ParseOptions {
+            dialect: lexer::Dialect::Json5,
+            ..parser::ParseOptions::default()
+        };
+        let value = parser::parse_with(&tokens, &options).unwrap();
+        let format_options = FormatOptions {
+            max_width: 80,
+            ..FormatOptions::default()
+        };
+
+        assert_eq!(
+            format_cst(cst::build(raw, &tokens, value), &format_options),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_json_lines() {
+        let raw = "{\"b\": [1,\n 2], \"a\": null}\n\n\"foo\"\n{\"\\u00e9\": \"\\/\"}\n";
+
+        let tokens = lexer::lex(raw);
+        let values = parser::parse_lines(&tokens).unwrap();
+        let formatted = format_json_lines(cst::build_lines(raw, &tokens, values), &indent(4));
+
+        assert_eq!(
+            formatted,
+            "{\"b\":[1,2],\"a\":null}\n\"foo\"\n{\"\\u00e9\":\"\\/\"}\n"
+        );
+    }
+
+    #[test]
+    fn test_line_endings() {
+        let raw = "{\r\n  \"a\": [1,\r\n 2]\r\n}\r\n";
+        let line_ending = LineEnding::detect(raw);
+
+        assert_eq!(line_ending, LineEnding::Crlf);
+        assert_eq!(LineEnding::detect("[1,\r2]"), LineEnding::Cr);
+        assert_eq!(LineEnding::detect("[1,\n2]\r\n"), LineEnding::Lf);
+        assert_eq!(LineEnding::detect("[]"), LineEnding::Lf);
+
+        let options = FormatOptions {
+            indent_length: 2,
+            line_ending,
+            ..FormatOptions::default()
+        };
+
+        let tokens = lexer::lex(raw);
+        let value = parser::parse(&tokens).unwrap();
+        let formatted = format_json(value, &options);
+
+        assert_eq!(
+            formatted,
+            "{\r\n  \"a\": [\r\n    1,\r\n    2\r\n  ]\r\n}\r\n"
+        );
+    }
+
+    #[test]
+    fn test_comments() {
+        let raw = "// head\r\n{\"b\": [1, // one\r\n2,],\r\n  /* about a */ \"a\": {},\r\n// left over\r\n}";
+        let tokens = lexer::lex_with(raw, lexer::Dialect::Jsonc);
+        let options = parser::ParseOptions {
+            dialect: lexer::Dialect::Jsonc,
+            ..parser::ParseOptions::default()
+        };
+
+        let expected = [
+            "/* head */{\"b\":[1,/* one */2],/* about a */\"a\":{}/* left over */}\n",
+            "// head\r\n{\r\n  /* about a */\r\n  \"a\": {\r\n  },\r\n  \"b\": [\r\n    1, // one\r\n    2\r\n  ]\r\n  // left over\r\n}\r\n",
+        ];
+
+        for (i, expected) in expected.iter().enumerate() {
+            let format_options = FormatOptions {
+                indent_length: i * 2,
+                sort_keys: i == 1,
+                line_ending: LineEnding::detect(expected),
+                ..FormatOptions::default()
+            };
+
+            let value = parser::parse_with(&tokens, &options).unwrap();
+            let formatted = format_cst(cst::build(raw, &tokens, value), &format_options);
+
+            assert_eq!(formatted, *expected);
+        }
+    }
+
+    #[test]
+    fn test_comments_reformat() {
+        let options = parser::ParseOptions {
+            dialect: lexer::Dialect::Jsonc,
+            ..parser::ParseOptions::default()
+        };
+        let format = |raw: &str| {
+            let tokens = lexer::lex_with(raw, lexer::Dialect::Jsonc);
+            let value = parser::parse_with(&tokens, &options).unwrap();
+
+            format_cst(cst::build(raw, &tokens, value), &indent(4))
+        };
+
+        let cases = [
+            (
+                "[1 // c\n/* m\n x */\n]",
+                "[\n    1 // c\n    /* m\n x */\n]\n",
+            ),
+            ("[1\n// a\n, 2]", "[\n    1,\n    // a\n    2\n]\n"),
+            (
+                "{\"a\" // k\n: /* v\n */ 1, \"b\": 2}",
+                "{\n    \"a\": 1, // k\n    /* v\n */\n    \"b\": 2\n}\n",
+            ),
+            (
+                "[ 1/* e\n */,/* d */]",
+                "[\n    1 /* e\n */\n    /* d */\n]\n",
+            ),
+        ];
+
+        for (raw, expected) in cases {
+            let formatted = format(raw);
+
+            assert_eq!(formatted, expected, "{}", raw);
+            assert_eq!(format(&formatted), formatted, "{}", raw);
+        }
+    }
 }
@@ -1,12 +1,92 @@
-use std::fmt;
+use std::{
+    borrow::Cow,
+    fmt,
+    iter::Peekable,
+    str::{CharIndices, Chars},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Dialect {
+    #[default]
+    Json,
+    Jsonc,
+    Json5,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum NumberError {
+    LeadingPlus,
+    LeadingZero,
+    MissingInteger,
+    /// A sign with nothing after it
+    MissingDigit(char),
+    MissingFraction,
+    MissingExponent,
+    MissingHexDigit,
+    NotFinite,
+    UnexpectedCharacter(char),
+}
+
+impl fmt::Display for NumberError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> std::fmt::Result {
+        match self {
+            NumberError::LeadingPlus => write!(f, "a leading '+' is not allowed"),
+            NumberError::LeadingZero => write!(f, "leading zeros are not allowed"),
+            NumberError::MissingInteger => {
+                write!(f, "expected a digit before the decimal point")
+            }
+            NumberError::MissingDigit(sign) => write!(f, "expected a digit after '{}'", sign),
+            NumberError::MissingFraction => {
+                write!(f, "expected a digit after the decimal point")
+            }
+            NumberError::MissingExponent => write!(f, "expected a digit in the exponent"),
+            NumberError::MissingHexDigit => write!(f, "expected a hex digit after 0x"),
+            NumberError::NotFinite => write!(f, "NaN and Infinity are not allowed"),
+            NumberError::UnexpectedCharacter(c) => write!(f, "unexpected character '{}'", c),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum StringError {
+    InvalidEscape,
+    InvalidJson5Escape,
+    InvalidUnicodeEscape,
+    LoneSurrogate,
+    ControlCharacter,
+}
+
+impl fmt::Display for StringError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> std::fmt::Result {
+        match self {
+            StringError::InvalidEscape => write!(
+                f,
+                "only \\\", \\\\, \\/, \\b, \\f, \\n, \\r, \\t and \\uXXXX escapes are allowed"
+            ),
+            StringError::InvalidJson5Escape => write!(
+                f,
+                "only \\', \\\", \\\\, \\/, \\b, \\f, \\n, \\r, \\t, \\v, \\0, \\xHH, \\uXXXX and escaped line ends are allowed"
+            ),
+            StringError::InvalidUnicodeEscape => write!(f, "expected 4 hex digits after \\u"),
+            StringError::LoneSurrogate => write!(f, "UTF-16 surrogates must come in pairs"),
+            StringError::ControlCharacter => write!(f, "control characters must be escaped"),
+        }
+    }
+}
 
 #[derive(Debug, PartialEq)]
 pub enum TokenType<'a> {
     Null,
     Bool(bool),
-    Number(f64),
-    String(&'a str),
+    Number(&'a str),
+    String(Cow<'a, str>),
     Invalid(&'a str),
+    InvalidNumber(&'a str, NumberError),
+    InvalidString(&'a str, StringError),
+    /// The text up to the line end or the end of the file, `true` for the latter
+    UnterminatedString(&'a str, bool),
+    Identifier(&'a str),
+    Comment(&'a str),
     OpenCurly,
     CloseCurly,
     OpenSquare,
@@ -23,6 +103,20 @@ impl fmt::Display for TokenType<'_> {
             TokenType::Number(n) => write!(f, "{}", n),
             TokenType::String(s) => write!(f, "{}", s),
             TokenType::Invalid(s) => write!(f, "{}", s),
+            TokenType::InvalidNumber(s, _) => write!(f, "{}", s),
+            TokenType::InvalidString(s, _) => {
+                for c in s.chars() {
+                    match c.is_control() {
+                        true => write!(f, "{}", c.escape_default())?,
+                        false => write!(f, "{}", c)?,
+                    }
+                }
+
+                Ok(())
+            }
+            TokenType::UnterminatedString(s, _) => write!(f, "{}", s),
+            TokenType::Identifier(s) => write!(f, "{}", s),
+            TokenType::Comment(s) => write!(f, "{}", s),
             TokenType::OpenCurly => write!(f, "{{"),
             TokenType::CloseCurly => write!(f, "}}"),
             TokenType::OpenSquare => write!(f, "["),
@@ -38,10 +132,11 @@ pub struct Token<'a> {
     pub token_type: TokenType<'a>,
     pub line: usize,
     pub col: usize,
+    pub offset: usize,
 }
 
 impl<'a> Token<'a> {
-    fn from_punctuator(c: char, line: usize, col: usize) -> Token<'a> {
+    fn from_punctuator(c: char, line: usize, col: usize, offset: usize) -> Token<'a> {
         let token_type = match c {
             '{' => TokenType::OpenCurly,
             '}' => TokenType::CloseCurly,
@@ -56,118 +151,501 @@ impl<'a> Token<'a> {
             token_type,
             line,
             col,
+            offset,
         }
     }
 
-    fn from_quoted_str(string: &'a str, line: usize, col: usize) -> Token<'a> {
+    fn from_quoted_str(
+        string: &'a str,
+        line: usize,
+        col: usize,
+        offset: usize,
+        dialect: Dialect,
+    ) -> Token<'a> {
         let token_string = &string[1..string.len() - 1];
 
-        Token {
-            token_type: TokenType::String(token_string),
-            line,
-            col,
+        match decode_string(token_string, dialect) {
+            Ok(decoded) => Token {
+                token_type: TokenType::String(decoded),
+                line,
+                col,
+                offset,
+            },
+            Err((start, end, string_error)) => {
+                let (line, col) = position_after(line, col, &string[..1 + start]);
+
+                Token {
+                    token_type: TokenType::InvalidString(&token_string[start..end], string_error),
+                    line,
+                    col,
+                    offset: offset + 1 + start,
+                }
+            }
         }
     }
 
-    fn from_key_or_val(symbol: &str, line: usize, col: usize) -> Token {
-        fn get_token_type(symbol: &str) -> TokenType {
-            match symbol.parse::<f64>() {
-                Ok(number) => TokenType::Number(number),
-                _ => match symbol {
-                    "null" => TokenType::Null,
-                    "true" => TokenType::Bool(true),
-                    "false" => TokenType::Bool(false),
-                    _ => TokenType::Invalid(symbol),
+    fn from_key_or_val(
+        symbol: &str,
+        line: usize,
+        col: usize,
+        offset: usize,
+        dialect: Dialect,
+    ) -> Token<'_> {
+        fn get_token_type(symbol: &str, dialect: Dialect) -> TokenType<'_> {
+            match symbol {
+                "null" => TokenType::Null,
+                "true" => TokenType::Bool(true),
+                "false" => TokenType::Bool(false),
+                _ if is_number_like(symbol) => match scan_number(symbol, dialect) {
+                    Ok(()) => TokenType::Number(symbol),
+                    Err(number_error) => TokenType::InvalidNumber(symbol, number_error),
                 },
+                _ if dialect == Dialect::Json5 && is_identifier(symbol) => {
+                    TokenType::Identifier(symbol)
+                }
+                _ => TokenType::Invalid(symbol),
             }
         }
 
-        let token_type = get_token_type(symbol);
+        let token_type = get_token_type(symbol, dialect);
 
         Token {
             token_type,
             line,
             col,
+            offset,
         }
     }
+
+    /// The offset just past the token in `source`, the text it was lexed from.
+    pub fn end(&self, source: &str) -> usize {
+        let length = match self.token_type {
+            TokenType::Null => 4,
+            TokenType::Bool(b) => b.to_string().len(),
+            TokenType::Number(s)
+            | TokenType::Invalid(s)
+            | TokenType::InvalidNumber(s, _)
+            | TokenType::InvalidString(s, _)
+            | TokenType::UnterminatedString(s, _)
+            | TokenType::Identifier(s)
+            | TokenType::Comment(s) => s.len(),
+            TokenType::String(_) => {
+                let mut cursor = Cursor::new(&source[self.offset..]);
+                let quote = cursor.bump().unwrap_or('"');
+
+                match scan_string(&mut cursor, quote, Dialect::Json5) {
+                    Ok(end) | Err(end) => end,
+                }
+            }
+            _ => 1,
+        };
+
+        self.offset + length
+    }
 }
 
-fn is_punctuator(c: char) -> bool {
-    c == '{' || c == '}' || c == '[' || c == ']' || c == ':' || c == ','
+fn is_number_like(symbol: &str) -> bool {
+    let unsigned = symbol.trim_start_matches(['-', '+']);
+
+    match symbol.chars().next() {
+        Some('-' | '+' | '.') => true,
+        Some(c) if c.is_ascii_digit() => true,
+        _ => is_not_finite(unsigned),
+    }
 }
 
-fn is_quote(c: char) -> bool {
-    c == '"'
+fn is_not_finite(unsigned: &str) -> bool {
+    let lower = unsigned.to_ascii_lowercase();
+
+    lower == "inf" || lower == "infinity" || lower == "nan"
 }
 
-pub fn lex(s: &str) -> Vec<Token> {
-    let mut tokens = vec![];
+fn is_identifier(symbol: &str) -> bool {
+    let mut chars = symbol.chars();
 
-    let mut start: usize = 0;
-    let mut building = false;
-    let mut inside_quotes = false;
-    let mut prev_char_escape = false;
+    chars
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '$' || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '$' || c == '_')
+}
 
-    for (line_no, line_str) in s.split_terminator('\n').enumerate() {
-        for (col_no, c) in line_str.chars().enumerate() {
-            if !building {
-                if c.is_whitespace() {
-                    continue;
-                }
+fn skip_digits(chars: &mut Peekable<Chars>) -> usize {
+    let mut count = 0;
+
+    while chars.next_if(|c| c.is_ascii_digit()).is_some() {
+        count += 1;
+    }
 
-                start = col_no;
-                building = true;
+    count
+}
+
+fn scan_hex_number(digits: &str) -> Result<(), NumberError> {
+    match digits.chars().find(|c| !c.is_ascii_hexdigit()) {
+        _ if digits.is_empty() => Err(NumberError::MissingHexDigit),
+        Some(c) => Err(NumberError::UnexpectedCharacter(c)),
+        None => Ok(()),
+    }
+}
+
+fn scan_number(symbol: &str, dialect: Dialect) -> Result<(), NumberError> {
+    let json5 = dialect == Dialect::Json5;
+    let unsigned = symbol.strip_prefix(['-', '+']).unwrap_or(symbol);
+
+    if json5 && (unsigned == "Infinity" || unsigned == "NaN") {
+        return Ok(());
+    }
+
+    if is_not_finite(symbol.trim_start_matches(['-', '+'])) {
+        return Err(NumberError::NotFinite);
+    }
+
+    if !json5 && symbol.starts_with('+') {
+        return Err(NumberError::LeadingPlus);
+    }
+
+    if let Some(digits) = unsigned.strip_prefix("0x").or(unsigned.strip_prefix("0X")) {
+        if json5 {
+            return scan_hex_number(digits);
+        }
+    }
+
+    let mut chars = unsigned.chars().peekable();
+
+    let (integer_digits, point) = match chars.next() {
+        Some('0') => {
+            if chars.peek().is_some_and(|c| c.is_ascii_digit()) {
+                return Err(NumberError::LeadingZero);
             }
 
-            if !inside_quotes {
-                if is_punctuator(c) {
-                    if start != col_no {
-                        let token =
-                            Token::from_key_or_val(&line_str[start..col_no], line_no, start);
+            (1, false)
+        }
+        Some(c) if c.is_ascii_digit() => (1 + skip_digits(&mut chars), false),
+        Some('.') if json5 => (0, true),
+        Some('.') => return Err(NumberError::MissingInteger),
+        None => {
+            return Err(NumberError::MissingDigit(
+                symbol.chars().next().unwrap_or('-'),
+            ))
+        }
+        Some(c) => return Err(NumberError::UnexpectedCharacter(c)),
+    };
 
-                        tokens.push(token);
-                    }
+    // JSON5 allows either side of the decimal point to be empty, but not both
+    if (point || chars.next_if_eq(&'.').is_some())
+        && skip_digits(&mut chars) == 0
+        && (!json5 || integer_digits == 0)
+    {
+        return Err(match integer_digits {
+            0 => NumberError::MissingInteger,
+            _ => NumberError::MissingFraction,
+        });
+    }
+
+    if chars.next_if(|c| *c == 'e' || *c == 'E').is_some() {
+        chars.next_if(|c| *c == '+' || *c == '-');
+
+        if skip_digits(&mut chars) == 0 {
+            return Err(NumberError::MissingExponent);
+        }
+    }
 
-                    tokens.push(Token::from_punctuator(c, line_no, col_no));
-                    building = false;
-                } else if c.is_whitespace() {
-                    let token = Token::from_key_or_val(&line_str[start..col_no], line_no, start);
+    match chars.next() {
+        Some(c) => Err(NumberError::UnexpectedCharacter(c)),
+        None => Ok(()),
+    }
+}
 
-                    tokens.push(token);
-                    building = false;
-                } else if col_no == line_str.len() - 1 {
-                    let token =
-                        Token::from_key_or_val(&line_str[start..col_no + 1], line_no, start);
+fn hex_escape(string: &str, start: usize) -> Result<u32, (usize, usize, StringError)> {
+    let digits: String = string[start + 2..].chars().take(4).collect();
 
-                    tokens.push(token);
-                    building = false;
+    match digits.len() == 4 && digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        true => Ok(u32::from_str_radix(&digits, 16).unwrap()),
+        false => Err((
+            start,
+            start + 2 + digits.len(),
+            StringError::InvalidUnicodeEscape,
+        )),
+    }
+}
+
+/// Whether `raw`, a quoted string as it is in the source, is also valid JSON
+pub fn is_json_string(raw: &str) -> bool {
+    raw.len() >= 2
+        && raw.starts_with('"')
+        && raw.ends_with('"')
+        && decode_string(&raw[1..raw.len() - 1], Dialect::Json).is_ok()
+}
+
+fn decode_string(
+    string: &str,
+    dialect: Dialect,
+) -> Result<Cow<'_, str>, (usize, usize, StringError)> {
+    let json5 = dialect == Dialect::Json5;
+    let invalid_escape = || match json5 {
+        true => StringError::InvalidJson5Escape,
+        false => StringError::InvalidEscape,
+    };
+
+    if !string.contains(|c: char| c == '\\' || (c as u32) < 0x20) {
+        return Ok(Cow::Borrowed(string));
+    }
+
+    let mut decoded = String::with_capacity(string.len());
+    let mut chars = string.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        if !json5 && (c as u32) < 0x20 {
+            return Err((i, i + c.len_utf8(), StringError::ControlCharacter));
+        }
+
+        if c != '\\' {
+            decoded.push(c);
+            continue;
+        }
+
+        let escaped = match chars.next() {
+            Some((_, escaped)) => escaped,
+            None => return Err((i, i + 1, invalid_escape())),
+        };
+
+        match escaped {
+            '"' | '\\' | '/' => decoded.push(escaped),
+            'b' => decoded.push('\u{8}'),
+            'f' => decoded.push('\u{c}'),
+            'n' => decoded.push('\n'),
+            'r' => decoded.push('\r'),
+            't' => decoded.push('\t'),
+            '\'' | 'v' | '0' | 'x' | '\n' | '\r' | '\u{2028}' | '\u{2029}' if json5 => {
+                match escaped {
+                    '\'' => decoded.push('\''),
+                    'v' => decoded.push('\u{b}'),
+                    '0' if chars.peek().is_some_and(|&(_, c)| c.is_ascii_digit()) => {
+                        return Err((i, i + 3, invalid_escape()))
+                    }
+                    '0' => decoded.push('\0'),
+                    'x' => {
+                        let digits: String = string[i + 2..].chars().take(2).collect();
+
+                        match digits.len() == 2 && digits.chars().all(|c| c.is_ascii_hexdigit()) {
+                            true => decoded.push(u8::from_str_radix(&digits, 16).unwrap() as char),
+                            false => return Err((i, i + 2 + digits.len(), invalid_escape())),
+                        }
+
+                        chars.nth(1);
+                    }
+                    // An escaped line terminator continues the string on the next line
+                    '\r' => {
+                        chars.next_if(|&(_, c)| c == '\n');
+                    }
+                    _ => {}
                 }
             }
+            'u' => {
+                let high = hex_escape(string, i)?;
+                chars.nth(3);
 
-            if is_quote(c) {
-                if prev_char_escape {
-                    prev_char_escape = false;
-                    continue;
-                }
+                let code = match high {
+                    0xD800..=0xDBFF => match string[i + 6..].starts_with("\\u") {
+                        true => match hex_escape(string, i + 6)? {
+                            low @ 0xDC00..=0xDFFF => {
+                                chars.nth(5);
+                                0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+                            }
+                            _ => return Err((i, i + 6, StringError::LoneSurrogate)),
+                        },
+                        false => return Err((i, i + 6, StringError::LoneSurrogate)),
+                    },
+                    0xDC00..=0xDFFF => return Err((i, i + 6, StringError::LoneSurrogate)),
+                    code => code,
+                };
 
-                if !inside_quotes {
-                    inside_quotes = true;
-                    continue;
-                }
+                decoded.push(char::from_u32(code).unwrap());
+            }
+            _ => return Err((i, i + 1 + escaped.len_utf8(), invalid_escape())),
+        }
+    }
+
+    Ok(Cow::Owned(decoded))
+}
+
+fn is_punctuator(c: char) -> bool {
+    c == '{' || c == '}' || c == '[' || c == ']' || c == ':' || c == ','
+}
+
+fn is_quote(c: char, dialect: Dialect) -> bool {
+    c == '"' || (c == '\'' && dialect == Dialect::Json5)
+}
+
+fn starts_comment(rest: &str, dialect: Dialect) -> bool {
+    dialect != Dialect::Json && (rest.starts_with("//") || rest.starts_with("/*"))
+}
+
+struct Cursor<'a> {
+    source: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+    line: usize,
+    col: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(source: &'a str) -> Cursor<'a> {
+        Cursor {
+            source,
+            chars: source.char_indices().peekable(),
+            line: 0,
+            col: 0,
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().map(|&(_, c)| c)
+    }
+
+    fn offset(&mut self) -> usize {
+        self.chars.peek().map_or(self.source.len(), |&(i, _)| i)
+    }
 
-                tokens.push(Token::from_quoted_str(
-                    &line_str[start..col_no + 1],
-                    line_no,
-                    start,
-                ));
+    fn rest(&mut self) -> &'a str {
+        &self.source[self.offset()..]
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let (_, c) = self.chars.next()?;
+
+        match c {
+            '\n' => self.new_line(),
+            '\r' if self.peek() != Some('\n') => self.new_line(),
+            _ => self.col += 1,
+        }
+
+        Some(c)
+    }
+
+    fn bump_line_end(&mut self) {
+        if self.bump() == Some('\r') && self.peek() == Some('\n') {
+            self.bump();
+        }
+    }
+
+    fn new_line(&mut self) {
+        self.line += 1;
+        self.col = 0;
+    }
+}
+
+/// The line and column after `text` when it starts at `line` and `col`, JSON5
+/// strings may go on over escaped line ends
+pub(crate) fn position_after(line: usize, col: usize, text: &str) -> (usize, usize) {
+    match text.rfind(['\n', '\r']) {
+        Some(i) => {
+            let line_ends = text.matches(['\n', '\r']).count() - text.matches("\r\n").count();
+            (line + line_ends, text[i + 1..].chars().count())
+        }
+        None => (line, col + text.chars().count()),
+    }
+}
 
-                building = false;
-                inside_quotes = false;
+fn is_line_end(c: Option<char>) -> bool {
+    matches!(c, None | Some('\n') | Some('\r'))
+}
+
+fn scan_string(cursor: &mut Cursor, quote: char, dialect: Dialect) -> Result<usize, usize> {
+    loop {
+        if is_line_end(cursor.peek()) {
+            return Err(cursor.offset());
+        }
+
+        match cursor.bump() {
+            Some('\\') if !is_line_end(cursor.peek()) => {
+                cursor.bump();
             }
+            Some('\\') if dialect == Dialect::Json5 && cursor.peek().is_some() => {
+                cursor.bump_line_end();
+            }
+            Some(c) if c == quote => return Ok(cursor.offset()),
+            _ => {}
+        }
+    }
+}
+
+fn scan_comment(cursor: &mut Cursor) -> Option<usize> {
+    let end = match cursor.rest().starts_with("//") {
+        true => cursor
+            .rest()
+            .find(['\n', '\r'])
+            .unwrap_or(cursor.rest().len()),
+        false => cursor.rest()[2..].find("*/")? + 4,
+    };
+
+    let end = cursor.offset() + end;
+
+    while cursor.offset() < end {
+        cursor.bump();
+    }
+
+    Some(end)
+}
+
+fn is_symbol_char(c: char, dialect: Dialect) -> bool {
+    !c.is_whitespace() && !is_punctuator(c) && !is_quote(c, dialect)
+}
+
+pub fn lex(s: &str) -> Vec<Token<'_>> {
+    lex_with(s, Dialect::Json)
+}
+
+pub fn lex_with(s: &str, dialect: Dialect) -> Vec<Token<'_>> {
+    let mut tokens = vec![];
+    let mut cursor = Cursor::new(s);
+
+    while let Some(c) = cursor.peek() {
+        let (line, col, offset) = (cursor.line, cursor.col, cursor.offset());
+
+        if c.is_whitespace() {
+            cursor.bump();
+        } else if is_punctuator(c) {
+            cursor.bump();
+            tokens.push(Token::from_punctuator(c, line, col, offset));
+        } else if is_quote(c, dialect) {
+            cursor.bump();
+
+            let token = match scan_string(&mut cursor, c, dialect) {
+                Ok(end) => Token::from_quoted_str(&s[offset..end], line, col, offset, dialect),
+                Err(end) => Token {
+                    token_type: TokenType::UnterminatedString(&s[offset..end], end == s.len()),
+                    line,
+                    col,
+                    offset,
+                },
+            };
+
+            tokens.push(token);
+        } else if starts_comment(cursor.rest(), dialect) {
+            let token_type = match scan_comment(&mut cursor) {
+                Some(end) => TokenType::Comment(&s[offset..end]),
+                None => {
+                    while cursor.bump().is_some() {}
+
+                    TokenType::Invalid(&s[offset..offset + 2])
+                }
+            };
 
-            if c == '\\' {
-                prev_char_escape = true
+            tokens.push(Token {
+                token_type,
+                line,
+                col,
+                offset,
+            });
+        } else {
+            while cursor.peek().is_some_and(|c| is_symbol_char(c, dialect))
+                && !starts_comment(cursor.rest(), dialect)
+            {
+                cursor.bump();
             }
+
+            let symbol = &s[offset..cursor.offset()];
+
+            tokens.push(Token::from_key_or_val(symbol, line, col, offset, dialect));
         }
     }
 
@@ -184,29 +662,34 @@ mod tests {
 
         let expected = vec![
             Token {
-                token_type: TokenType::String("foo"),
+                token_type: TokenType::String("foo".into()),
                 line: 0,
                 col: 0,
+                offset: 0,
             },
             Token {
                 token_type: TokenType::Colon,
                 line: 0,
                 col: 6,
+                offset: 6,
             },
             Token {
                 token_type: TokenType::OpenSquare,
                 line: 1,
                 col: 1,
+                offset: 10,
             },
             Token {
-                token_type: TokenType::String("bar"),
+                token_type: TokenType::String("bar".into()),
                 line: 1,
                 col: 3,
+                offset: 12,
             },
             Token {
                 token_type: TokenType::CloseCurly,
                 line: 1,
                 col: 9,
+                offset: 18,
             },
         ];
 
@@ -222,16 +705,19 @@ mod tests {
                 token_type: TokenType::OpenCurly,
                 line: 1,
                 col: 1,
+                offset: 2,
             },
             Token {
-                token_type: TokenType::String("bar"),
+                token_type: TokenType::String("bar".into()),
                 line: 1,
                 col: 3,
+                offset: 4,
             },
             Token {
                 token_type: TokenType::CloseSquare,
                 line: 1,
                 col: 9,
+                offset: 10,
             },
         ];
 
@@ -247,21 +733,25 @@ mod tests {
                 token_type: TokenType::OpenCurly,
                 line: 1,
                 col: 1,
+                offset: 2,
             },
             Token {
-                token_type: TokenType::String("bar"),
+                token_type: TokenType::String("bar".into()),
                 line: 1,
                 col: 2,
+                offset: 3,
             },
             Token {
                 token_type: TokenType::CloseSquare,
                 line: 1,
                 col: 8,
+                offset: 9,
             },
             Token {
                 token_type: TokenType::Comma,
                 line: 1,
                 col: 10,
+                offset: 11,
             },
         ];
 
@@ -277,16 +767,19 @@ mod tests {
                 token_type: TokenType::OpenCurly,
                 line: 1,
                 col: 1,
+                offset: 2,
             },
             Token {
-                token_type: TokenType::String("bar"),
+                token_type: TokenType::String("bar".into()),
                 line: 1,
                 col: 2,
+                offset: 3,
             },
             Token {
                 token_type: TokenType::CloseSquare,
                 line: 1,
                 col: 7,
+                offset: 8,
             },
         ];
 
@@ -302,26 +795,31 @@ mod tests {
                 token_type: TokenType::OpenCurly,
                 line: 1,
                 col: 1,
+                offset: 2,
             },
             Token {
                 token_type: TokenType::Invalid("bar"),
                 line: 1,
                 col: 2,
+                offset: 3,
             },
             Token {
                 token_type: TokenType::CloseSquare,
                 line: 1,
                 col: 5,
+                offset: 6,
             },
             Token {
                 token_type: TokenType::Colon,
                 line: 1,
                 col: 6,
+                offset: 7,
             },
             Token {
-                token_type: TokenType::String("foo"),
+                token_type: TokenType::String("foo".into()),
                 line: 2,
                 col: 0,
+                offset: 9,
             },
         ];
 
@@ -336,6 +834,7 @@ mod tests {
             token_type: TokenType::Invalid("bar"),
             line: 0,
             col: 0,
+            offset: 0,
         }];
 
         assert_eq!(tokens, expected);
@@ -346,9 +845,10 @@ mod tests {
         let tokens = lex("\"bar\"");
 
         let expected = vec![Token {
-            token_type: TokenType::String("bar"),
+            token_type: TokenType::String("bar".into()),
             line: 0,
             col: 0,
+            offset: 0,
         }];
 
         assert_eq!(tokens, expected);
@@ -359,9 +859,10 @@ mod tests {
         let tokens = lex("2345");
 
         let expected = vec![Token {
-            token_type: TokenType::Number(2345.0),
+            token_type: TokenType::Number("2345"),
             line: 0,
             col: 0,
+            offset: 0,
         }];
 
         assert_eq!(tokens, expected);
@@ -373,14 +874,16 @@ mod tests {
 
         let expected = vec![
             Token {
-                token_type: TokenType::Number(2345.0),
+                token_type: TokenType::Number("2345"),
                 line: 0,
                 col: 0,
+                offset: 0,
             },
             Token {
                 token_type: TokenType::CloseCurly,
                 line: 0,
                 col: 4,
+                offset: 4,
             },
         ];
 
@@ -393,19 +896,22 @@ mod tests {
 
         let expected = vec![
             Token {
-                token_type: TokenType::Number(2345.0),
+                token_type: TokenType::Number("2345"),
                 line: 0,
                 col: 0,
+                offset: 0,
             },
             Token {
                 token_type: TokenType::CloseCurly,
                 line: 0,
                 col: 10,
+                offset: 10,
             },
             Token {
-                token_type: TokenType::Number(456.0),
+                token_type: TokenType::Number("456"),
                 line: 0,
                 col: 12,
+                offset: 12,
             },
         ];
 
@@ -418,22 +924,389 @@ mod tests {
 
         let expected = vec![
             Token {
-                token_type: TokenType::String("foo"),
+                token_type: TokenType::String("foo".into()),
                 line: 0,
                 col: 0,
+                offset: 0,
             },
             Token {
                 token_type: TokenType::Colon,
                 line: 0,
                 col: 5,
+                offset: 5,
             },
             Token {
-                token_type: TokenType::String("{\\\"bar\\\":0}"),
+                token_type: TokenType::String("{\"bar\":0}".into()),
                 line: 0,
                 col: 7,
+                offset: 7,
             },
         ];
 
         assert_eq!(tokens, expected);
     }
+
+    #[test]
+    fn test_lexer_numbers() {
+        let cases = vec![
+            ("0", TokenType::Number("0")),
+            ("-0.5e-10", TokenType::Number("-0.5e-10")),
+            ("1E+2", TokenType::Number("1E+2")),
+            (
+                "12345678901234567890",
+                TokenType::Number("12345678901234567890"),
+            ),
+            (
+                "+1",
+                TokenType::InvalidNumber("+1", NumberError::LeadingPlus),
+            ),
+            (
+                "01",
+                TokenType::InvalidNumber("01", NumberError::LeadingZero),
+            ),
+            (
+                "-00",
+                TokenType::InvalidNumber("-00", NumberError::LeadingZero),
+            ),
+            (
+                ".5",
+                TokenType::InvalidNumber(".5", NumberError::MissingInteger),
+            ),
+            (
+                "-",
+                TokenType::InvalidNumber("-", NumberError::MissingDigit('-')),
+            ),
+            (
+                "1.",
+                TokenType::InvalidNumber("1.", NumberError::MissingFraction),
+            ),
+            (
+                "1.e3",
+                TokenType::InvalidNumber("1.e3", NumberError::MissingFraction),
+            ),
+            (
+                "1e",
+                TokenType::InvalidNumber("1e", NumberError::MissingExponent),
+            ),
+            (
+                "1e+",
+                TokenType::InvalidNumber("1e+", NumberError::MissingExponent),
+            ),
+            (
+                "inf",
+                TokenType::InvalidNumber("inf", NumberError::NotFinite),
+            ),
+            (
+                "NaN",
+                TokenType::InvalidNumber("NaN", NumberError::NotFinite),
+            ),
+            (
+                "-Infinity",
+                TokenType::InvalidNumber("-Infinity", NumberError::NotFinite),
+            ),
+            (
+                "0x10",
+                TokenType::InvalidNumber("0x10", NumberError::UnexpectedCharacter('x')),
+            ),
+            (
+                "1.5.2",
+                TokenType::InvalidNumber("1.5.2", NumberError::UnexpectedCharacter('.')),
+            ),
+            ("nul", TokenType::Invalid("nul")),
+        ];
+
+        for (raw, expected) in cases {
+            let tokens = lex(raw);
+
+            assert_eq!(
+                tokens,
+                vec![Token {
+                    token_type: expected,
+                    line: 0,
+                    col: 0,
+                    offset: 0,
+                }],
+                "{}",
+                raw
+            );
+        }
+    }
+
+    #[test]
+    fn test_lexer_strings() {
+        let cases = vec![
+            (r#""a\"b""#, TokenType::String("a\"b".into()), 0),
+            (r#""a\\""#, TokenType::String("a\\".into()), 0),
+            (r#""\n\u00e9""#, TokenType::String("\n\u{e9}".into()), 0),
+            (
+                r#""\ud83d\ude00""#,
+                TokenType::String("\u{1f600}".into()),
+                0,
+            ),
+            (
+                r#""ab\qc""#,
+                TokenType::InvalidString("\\q", StringError::InvalidEscape),
+                3,
+            ),
+            (
+                r#""x\u12G4""#,
+                TokenType::InvalidString("\\u12G4", StringError::InvalidUnicodeEscape),
+                2,
+            ),
+            (
+                r#""\u12""#,
+                TokenType::InvalidString("\\u12", StringError::InvalidUnicodeEscape),
+                1,
+            ),
+            (
+                r#""x\ud800y""#,
+                TokenType::InvalidString("\\ud800", StringError::LoneSurrogate),
+                2,
+            ),
+            (
+                r#""\ud800\u0041""#,
+                TokenType::InvalidString("\\ud800", StringError::LoneSurrogate),
+                1,
+            ),
+            (
+                r#""\udc00""#,
+                TokenType::InvalidString("\\udc00", StringError::LoneSurrogate),
+                1,
+            ),
+            (
+                "\"a\tb\"",
+                TokenType::InvalidString("\t", StringError::ControlCharacter),
+                2,
+            ),
+        ];
+
+        for (raw, expected, col) in cases {
+            let tokens = lex(raw);
+
+            assert_eq!(
+                tokens,
+                vec![Token {
+                    token_type: expected,
+                    line: 0,
+                    col,
+                    offset: col,
+                }],
+                "{}",
+                raw
+            );
+        }
+    }
+
+    #[test]
+    fn test_lexer_non_ascii() {
+        let tokens = lex("{\"é\": \"日本\",\n \"🦀\":ключ}");
+
+        let expected = vec![
+            (TokenType::OpenCurly, 0, 0, 0),
+            (TokenType::String("é".into()), 0, 1, 1),
+            (TokenType::Colon, 0, 4, 5),
+            (TokenType::String("日本".into()), 0, 6, 7),
+            (TokenType::Comma, 0, 10, 15),
+            (TokenType::String("🦀".into()), 1, 1, 18),
+            (TokenType::Colon, 1, 4, 24),
+            (TokenType::Invalid("ключ"), 1, 5, 25),
+            (TokenType::CloseCurly, 1, 9, 33),
+        ];
+
+        let expected: Vec<Token> = expected
+            .into_iter()
+            .map(|(token_type, line, col, offset)| Token {
+                token_type,
+                line,
+                col,
+                offset,
+            })
+            .collect();
+
+        assert_eq!(tokens, expected);
+    }
+
+    #[test]
+    fn test_lexer_line_endings() {
+        for line_ending in ["\n", "\r\n", "\r"] {
+            let raw = ["{", " \"foo\": 1,", "\t\"bar\":true", "}"].join(line_ending);
+            let tokens = lex(&raw);
+            let len = line_ending.len();
+
+            let expected = vec![
+                (TokenType::OpenCurly, 0, 0, 0),
+                (TokenType::String("foo".into()), 1, 1, 1 + len + 1),
+                (TokenType::Colon, 1, 6, 1 + len + 6),
+                (TokenType::Number("1"), 1, 8, 1 + len + 8),
+                (TokenType::Comma, 1, 9, 1 + len + 9),
+                (TokenType::String("bar".into()), 2, 1, 11 + 2 * len + 1),
+                (TokenType::Colon, 2, 6, 11 + 2 * len + 6),
+                (TokenType::Bool(true), 2, 7, 11 + 2 * len + 7),
+                (TokenType::CloseCurly, 3, 0, 22 + 3 * len),
+            ];
+
+            let expected: Vec<Token> = expected
+                .into_iter()
+                .map(|(token_type, line, col, offset)| Token {
+                    token_type,
+                    line,
+                    col,
+                    offset,
+                })
+                .collect();
+
+            assert_eq!(tokens, expected, "{:?}", line_ending);
+        }
+    }
+
+    #[test]
+    fn test_lexer_unterminated_string() {
+        let tokens = lex("[\"abc,\n \"d\\\n]");
+
+        let expected = vec![
+            Token {
+                token_type: TokenType::OpenSquare,
+                line: 0,
+                col: 0,
+                offset: 0,
+            },
+            Token {
+                token_type: TokenType::UnterminatedString("\"abc,", false),
+                line: 0,
+                col: 1,
+                offset: 1,
+            },
+            Token {
+                token_type: TokenType::UnterminatedString("\"d\\", false),
+                line: 1,
+                col: 1,
+                offset: 8,
+            },
+            Token {
+                token_type: TokenType::CloseSquare,
+                line: 2,
+                col: 0,
+                offset: 12,
+            },
+        ];
+
+        assert_eq!(tokens, expected);
+    }
+
+    #[test]
+    fn test_lexer_json5() {
+        let cases = vec![
+            ("0x1F", TokenType::Number("0x1F")),
+            ("-Infinity", TokenType::Number("-Infinity")),
+            ("NaN", TokenType::Number("NaN")),
+            ("+1", TokenType::Number("+1")),
+            (".5", TokenType::Number(".5")),
+            ("5.", TokenType::Number("5.")),
+            (
+                "0x",
+                TokenType::InvalidNumber("0x", NumberError::MissingHexDigit),
+            ),
+            (
+                "0xG",
+                TokenType::InvalidNumber("0xG", NumberError::UnexpectedCharacter('G')),
+            ),
+            (
+                ".",
+                TokenType::InvalidNumber(".", NumberError::MissingInteger),
+            ),
+            (
+                "01",
+                TokenType::InvalidNumber("01", NumberError::LeadingZero),
+            ),
+            (
+                "+",
+                TokenType::InvalidNumber("+", NumberError::MissingDigit('+')),
+            ),
+            ("$foo_1", TokenType::Identifier("$foo_1")),
+            ("'a\"b'", TokenType::String("a\"b".into())),
+            (r"'it\'s'", TokenType::String("it's".into())),
+            (r#""\x41\v\0""#, TokenType::String("A\u{b}\0".into())),
+            ("'a\\\r\nb'", TokenType::String("ab".into())),
+            ("\"a\tb\"", TokenType::String("a\tb".into())),
+            (
+                r"'\q'",
+                TokenType::InvalidString("\\q", StringError::InvalidJson5Escape),
+            ),
+        ];
+
+        for (raw, expected) in cases {
+            let tokens = lex_with(raw, Dialect::Json5);
+
+            assert_eq!(tokens[0].token_type, expected, "{}", raw);
+            assert_eq!(tokens.len(), 1, "{}", raw);
+        }
+
+        let tokens = lex_with("'a' 0x1F foo", Dialect::Json);
+        let token_types: Vec<&TokenType> = tokens.iter().map(|token| &token.token_type).collect();
+
+        assert_eq!(
+            token_types,
+            vec![
+                &TokenType::Invalid("'a'"),
+                &TokenType::InvalidNumber("0x1F", NumberError::UnexpectedCharacter('x')),
+                &TokenType::Invalid("foo"),
+            ]
+        );
+
+        let tokens = lex_with("['ab\\\ncd\\q']", Dialect::Json5);
+
+        assert_eq!((tokens[1].line, tokens[1].col, tokens[1].offset), (1, 2, 8));
+    }
+
+    #[test]
+    fn test_lexer_comments() {
+        let raw = "{// line\n\"a\":1/* block\n comment */,\"b\"://\n2}/* end";
+        let tokens = lex_with(raw, Dialect::Jsonc);
+        let token_types: Vec<&TokenType> = tokens.iter().map(|token| &token.token_type).collect();
+
+        assert_eq!(
+            token_types,
+            vec![
+                &TokenType::OpenCurly,
+                &TokenType::Comment("// line"),
+                &TokenType::String("a".into()),
+                &TokenType::Colon,
+                &TokenType::Number("1"),
+                &TokenType::Comment("/* block\n comment */"),
+                &TokenType::Comma,
+                &TokenType::String("b".into()),
+                &TokenType::Colon,
+                &TokenType::Comment("//"),
+                &TokenType::Number("2"),
+                &TokenType::CloseCurly,
+                &TokenType::Invalid("/*"),
+            ]
+        );
+
+        assert_eq!(
+            (tokens[6].line, tokens[6].col, tokens[6].offset),
+            (2, 11, 34)
+        );
+        assert_eq!(lex("// x")[0].token_type, TokenType::Invalid("//"));
+
+        let tokens = lex_with("[1 /*/ x */]", Dialect::Jsonc);
+
+        assert_eq!(tokens[2].token_type, TokenType::Comment("/*/ x */"));
+        assert_eq!(tokens.len(), 4);
+        assert_eq!(
+            lex_with("/*/", Dialect::Jsonc)[0].token_type,
+            TokenType::Invalid("/*")
+        );
+    }
+
+    #[test]
+    fn test_token_end() {
+        let raw = "[\"a\\\"b\", false, 1.5e3, 'c', x, \"d\n";
+        let ends: Vec<usize> = lex_with(raw, Dialect::Json5)
+            .iter()
+            .map(|token| token.end(raw))
+            .collect();
+
+        assert_eq!(ends, vec![1, 7, 8, 14, 15, 21, 22, 26, 27, 29, 30, 33]);
+    }
 }
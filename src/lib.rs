@@ -0,0 +1,41 @@
+//! Parser, validator and formatter behind the `joxide` CLI.
+//!
+//! ```
+//! use joxide::{format_json, lex, parse, FormatOptions};
+//!
+//! let tokens = lex("{\"hello\": [1, 2]}");
+//! let value = parse(&tokens).unwrap();
+//!
+//! let options = FormatOptions {
+//!     indent_length: 2,
+//!     ..FormatOptions::default()
+//! };
+//!
+//! let formatted = format_json(value, &options);
+//! assert_eq!(formatted, "{\n  \"hello\": [\n    1,\n    2\n  ]\n}\n");
+//!
+//! let tokens = lex("[1, 2");
+//! let errors = parse(&tokens).unwrap_err();
+//! assert_eq!(errors[0].to_string(), "File ended unexpectedly");
+//! ```
+
+extern crate unicode_width;
+
+pub mod cst;
+pub mod diagnostic;
+pub mod formatter;
+pub mod lexer;
+pub mod number;
+pub mod parser;
+
+pub use cst::{Cst, Node};
+pub use formatter::{
+    detect_indent, format_cst, format_json, format_json_lines, FormatOptions, IndentStyle,
+    LineEnding,
+};
+pub use lexer::{lex, lex_with, Dialect, Token, TokenType};
+pub use number::{Decimal, Number};
+pub use parser::{
+    parse, parse_lines, parse_lines_with, parse_with, Edit, Json, ParseError, ParseErrorType,
+    ParseOptions, Suggestion, SuggestionType,
+};
@@ -1,117 +1,411 @@
 extern crate argh;
 extern crate glob;
+extern crate ignore;
+extern crate joxide;
+extern crate toml;
+extern crate unicode_width;
 
-use crate::args::JoxideSubcommand;
-use glob::{glob, GlobError, Paths, PatternError};
-use pretty::eprint_parse_error;
-use std::{path::PathBuf, process::ExitCode};
+use crate::args::{DialectArg, IndentArg, IndentStyleArg, JoxideSubcommand, LineEndingArg};
+use argh::FromArgs;
+use config::{Configs, Settings};
+use glob::glob;
+use joxide::{cst, formatter, lexer, parser};
+use report::Reporter;
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+use walk::{Excludes, WalkOptions};
 
 mod args;
-mod diagnostic;
-mod formatter;
-mod lexer;
-mod parser;
+mod color;
+mod config;
+mod diff;
+mod file_types;
 mod pretty;
+mod report;
+mod walk;
+
+// Ordered by severity, the most severe failure decides the exit code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Failure {
+    Invalid,
+    Error,
+}
 
 fn main() -> ExitCode {
-    let args: args::JoxideArgs = argh::from_env();
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(exit_code) => return exit_code,
+    };
+
+    let (path_matchers, stdin_filename, error_format, color) = match args.sub_command {
+        JoxideSubcommand::Validate(ref validate_args) => (
+            &validate_args.paths,
+            &validate_args.stdin_filename,
+            validate_args.error_format,
+            validate_args.color,
+        ),
+        JoxideSubcommand::Format(ref format_args) => (
+            &format_args.paths,
+            &format_args.stdin_filename,
+            format_args.error_format,
+            format_args.color,
+        ),
+    };
 
-    let path_matchers = match args.sub_command {
-        JoxideSubcommand::Validate(ref validate_args) => &validate_args.paths,
-        JoxideSubcommand::Format(ref format_args) => &format_args.paths,
+    let mut reporter = Reporter::new(error_format, color::enabled(color, std::io::stderr()));
+    let mut configs = Configs::default();
+    let settings = Settings::from_args(&args.sub_command);
+
+    if let Err(message) = get_walk_options(&settings, None) {
+        reporter.error(&message, None);
+        reporter.finish();
+        return ExitCode::from(2);
+    }
+
+    let stdin = vec!["-".to_string()];
+    let path_matchers = match path_matchers.is_empty() {
+        true => &stdin,
+        false => path_matchers,
     };
 
-    let failures: Vec<Result<(), ()>> = path_matchers
+    let failure = path_matchers
         .iter()
-        .map(get_glob)
-        .map(|glob_result| process_glob(glob_result, &args.sub_command))
+        .filter_map(|path| match path.as_str() {
+            "-" => process_stdin(
+                stdin_filename,
+                &args.sub_command,
+                &mut reporter,
+                &mut configs,
+            )
+            .err(),
+            _ => process_glob(
+                path,
+                get_paths(path, &settings, &mut configs),
+                &args.sub_command,
+                &mut reporter,
+                &mut configs,
+            )
+            .err(),
+        })
+        .max();
+
+    reporter.finish();
+
+    match failure {
+        None => ExitCode::SUCCESS,
+        Some(Failure::Invalid) => ExitCode::from(1),
+        Some(Failure::Error) => ExitCode::from(2),
+    }
+}
+
+fn parse_args() -> Result<args::JoxideArgs, ExitCode> {
+    let strings: Vec<String> = std::env::args_os()
+        .skip(1)
+        .map(|arg| arg.to_string_lossy().into_owned())
         .collect();
 
-    match failures.len() {
-        0 => ExitCode::SUCCESS,
-        _ => ExitCode::FAILURE,
+    // argh takes "-" for an unknown switch, it is only positional after "--"
+    let (stdin, mut strs): (Vec<&str>, Vec<&str>) = strings
+        .iter()
+        .map(String::as_str)
+        .partition(|arg| *arg == "-");
+
+    if !stdin.is_empty() && !strs.contains(&"--") {
+        strs.push("--");
     }
+
+    strs.extend(stdin);
+
+    args::JoxideArgs::from_args(&["joxide"], &strs).map_err(|early_exit| match early_exit.status {
+        Ok(()) => {
+            println!("{}", early_exit.output);
+            ExitCode::SUCCESS
+        }
+        Err(()) => {
+            eprintln!(
+                "{}\nRun joxide --help for more information.",
+                early_exit.output
+            );
+            ExitCode::from(2)
+        }
+    })
 }
 
-fn get_glob(path: &String) -> Result<Paths, PatternError> {
-    match std::fs::metadata(path) {
-        Ok(metadata) => match metadata.is_dir() {
-            true => glob(&format!("{}/**/*.json", path)),
-            false => glob(path),
-        },
-        Err(_) => glob(path),
+// The options given on the command line, along with the ones in `config` when
+// searching a directory. Excludes add up, the other options on the command
+// line win over the config.
+fn get_walk_options(
+    settings: &Settings,
+    config: Option<(PathBuf, Settings)>,
+) -> Result<WalkOptions, String> {
+    let pattern_error = |err| format!("Invalid exclude pattern, reason: {}", err);
+    let mut excludes =
+        vec![Excludes::new(settings.exclude.as_deref().unwrap_or(&[])).map_err(pattern_error)?];
+
+    let settings = match config {
+        Some((dir, config_settings)) => {
+            let patterns = config_settings.exclude.as_deref().unwrap_or(&[]);
+
+            excludes.push(Excludes::in_dir(patterns, &dir).map_err(pattern_error)?);
+            settings.clone().or(&config_settings)
+        }
+        None => settings.clone(),
+    };
+
+    Ok(WalkOptions {
+        excludes,
+        extensions: settings.ext.unwrap_or_default(),
+        hidden: settings.hidden.unwrap_or(false),
+    })
+}
+
+// Directories are walked with the options in their config, anything else is a
+// glob pattern
+fn get_paths(
+    path: &str,
+    settings: &Settings,
+    configs: &mut Configs,
+) -> Result<Vec<Result<PathBuf, String>>, String> {
+    if std::fs::metadata(path).is_ok_and(|metadata| metadata.is_dir()) {
+        let config = configs.dir_settings(Path::new(path))?;
+        let walk_options = get_walk_options(settings, config)?;
+
+        return Ok(walk::walk_dir(Path::new(path), &walk_options));
     }
+
+    let walk_options = get_walk_options(settings, None)?;
+
+    let paths = glob(path).map_err(|err| format!("Invalid glob pattern, reason: {}", err))?;
+
+    Ok(paths
+        .filter(|entry| {
+            !entry
+                .as_ref()
+                .is_ok_and(|path| walk_options.is_excluded(path))
+        })
+        .map(|entry| {
+            entry.map_err(|err| format!("Unable to do a glob pattern match, reason: {}", err))
+        })
+        .collect())
 }
 
 fn process_glob(
-    glob_result: Result<Paths, PatternError>,
+    path: &str,
+    paths: Result<Vec<Result<PathBuf, String>>, String>,
     sub_command: &JoxideSubcommand,
-) -> Result<(), ()> {
-    match glob_result {
-        Ok(paths) => paths
-            .into_iter()
-            .map(|entry| process_glob_entry(entry, sub_command))
-            .filter(|result| result.is_err())
-            .collect(),
-        Err(err) => {
-            eprint!("Invalid glob pattern, reason: {}", err);
-            Err(())
+    reporter: &mut Reporter,
+    configs: &mut Configs,
+) -> Result<(), Failure> {
+    let paths = match paths {
+        Ok(paths) => paths,
+        Err(message) => {
+            reporter.error(&message, None);
+            return Err(Failure::Error);
         }
+    };
+
+    let results: Vec<Result<(), Failure>> = paths
+        .into_iter()
+        .map(|entry| process_glob_entry(entry, sub_command, reporter, configs))
+        .collect();
+
+    if results.is_empty() && std::fs::metadata(path).is_err() {
+        reporter.error(
+            &format!("No such file or no files matching '{}'", path),
+            Some(Path::new(path)),
+        );
+        return Err(Failure::Error);
+    }
+
+    match results.into_iter().filter_map(Result::err).max() {
+        Some(failure) => Err(failure),
+        None => Ok(()),
     }
 }
 
 fn process_glob_entry(
-    entry: Result<PathBuf, GlobError>,
+    entry: Result<PathBuf, String>,
     sub_command: &JoxideSubcommand,
-) -> Result<(), ()> {
+    reporter: &mut Reporter,
+    configs: &mut Configs,
+) -> Result<(), Failure> {
     match entry {
-        Ok(path) => process_file(&path, sub_command),
-        Err(err) => {
-            eprint!("Unable to do a glob pattern match, reason: {}", err);
-            Err(())
+        Ok(path) => process_file(&path, sub_command, reporter, configs),
+        Err(message) => {
+            reporter.error(&message, None);
+            Err(Failure::Error)
         }
     }
 }
 
-fn process_file(file_path: &PathBuf, sub_command: &JoxideSubcommand) -> Result<(), ()> {
+fn process_stdin(
+    stdin_filename: &Option<String>,
+    sub_command: &JoxideSubcommand,
+    reporter: &mut Reporter,
+    configs: &mut Configs,
+) -> Result<(), Failure> {
+    let mut raw = String::new();
+
+    if let Err(err) = std::io::stdin().read_to_string(&mut raw) {
+        reporter.error(&format!("Unable to read stdin, reason: {}", err), None);
+        return Err(Failure::Error);
+    }
+
+    let file_path = Path::new(stdin_filename.as_deref().unwrap_or("<stdin>"));
+
+    process_content(&raw, file_path, true, sub_command, reporter, configs)
+}
+
+fn process_file(
+    file_path: &PathBuf,
+    sub_command: &JoxideSubcommand,
+    reporter: &mut Reporter,
+    configs: &mut Configs,
+) -> Result<(), Failure> {
     let raw = match std::fs::read_to_string(file_path) {
         Ok(content) => content,
         Err(err) => {
-            eprintln!("Unable to open file, reason: {}", err);
-            return Err(());
+            reporter.error(
+                &format!("Unable to open file, reason: {}", err),
+                Some(file_path),
+            );
+            return Err(Failure::Error);
         }
     };
 
-    let tokens = lexer::lex(&raw);
+    process_content(&raw, file_path, false, sub_command, reporter, configs)
+}
+
+fn process_content(
+    raw: &str,
+    file_path: &Path,
+    stdin: bool,
+    sub_command: &JoxideSubcommand,
+    reporter: &mut Reporter,
+    configs: &mut Configs,
+) -> Result<(), Failure> {
+    // Options on the command line win over the ones in the config file, and
+    // those over the ones picked by the file name
+    let settings = match configs.settings(file_path) {
+        Ok(config_settings) => Settings::from_args(sub_command)
+            .or(&config_settings)
+            .or(&file_types::get_settings(file_path)),
+        Err(message) => {
+            reporter.error(&message, Some(file_path));
+            return Err(Failure::Error);
+        }
+    };
+
+    let dialect = match settings.dialect.unwrap_or(DialectArg::Json) {
+        DialectArg::Json => lexer::Dialect::Json,
+        DialectArg::Jsonc => lexer::Dialect::Jsonc,
+        DialectArg::Json5 => lexer::Dialect::Json5,
+    };
+
+    let tokens = lexer::lex_with(raw, dialect);
+    let parse_options = parser::ParseOptions {
+        max_errors: settings
+            .max_errors
+            .unwrap_or(parser::ParseOptions::default().max_errors),
+        dialect,
+    };
+
+    let json_lines = settings.json_lines.unwrap_or(false);
+    let parse_result = match json_lines {
+        true => parser::parse_lines_with(&tokens, &parse_options),
+        false => parser::parse_with(&tokens, &parse_options).map(|value| vec![value]),
+    };
 
-    let parsed_value = match parser::parse(&tokens) {
-        Ok(value) => value,
-        Err(parse_error) => {
-            eprint_parse_error(parse_error, &raw, file_path);
-            return Err(());
+    let parsed_values = match parse_result {
+        Ok(values) => values,
+        Err(parse_errors) => {
+            reporter.report(&parse_errors, raw, file_path);
+            return Err(Failure::Invalid);
         }
     };
 
     match sub_command {
-        JoxideSubcommand::Format(format_args) => format_file(parsed_value, format_args, file_path),
+        JoxideSubcommand::Format(format_args) => {
+            let options = get_format_options(&settings, raw);
+            let formatted = match json_lines {
+                true => formatter::format_json_lines(
+                    cst::build_lines(raw, &tokens, parsed_values),
+                    &options,
+                ),
+                false => parsed_values
+                    .into_iter()
+                    .map(|value| formatter::format_cst(cst::build(raw, &tokens, value), &options))
+                    .collect(),
+            };
+
+            output_formatted(&formatted, format_args, raw, file_path, stdin, reporter)
+        }
         JoxideSubcommand::Validate(_) => Ok(()),
     }
 }
 
-fn format_file(
-    value: parser::Json<'_>,
+fn get_format_options(settings: &Settings, raw: &str) -> formatter::FormatOptions {
+    let line_ending = match settings.line_ending.unwrap_or(LineEndingArg::Auto) {
+        LineEndingArg::Lf => formatter::LineEnding::Lf,
+        LineEndingArg::Crlf => formatter::LineEnding::Crlf,
+        LineEndingArg::Auto => formatter::LineEnding::detect(raw),
+    };
+
+    let indent_style = match settings.indent_style.unwrap_or(IndentStyleArg::Space) {
+        IndentStyleArg::Space => formatter::IndentStyle::Space,
+        IndentStyleArg::Tab => formatter::IndentStyle::Tab,
+    };
+
+    let (indent_style, indent_length) = match settings.indent {
+        Some(IndentArg::Auto) => formatter::detect_indent(raw),
+        None => None,
+    }
+    .unwrap_or((indent_style, settings.indent_length.unwrap_or(4)));
+
+    formatter::FormatOptions {
+        indent_length,
+        indent_style,
+        sort_keys: settings.sort_keys.unwrap_or(false),
+        line_ending,
+        max_width: settings.max_width.unwrap_or(0),
+    }
+}
+
+fn output_formatted(
+    formatted: &str,
     format_args: &args::FormatArgs,
-    file_path: &PathBuf,
-) -> Result<(), ()> {
-    let formatted = formatter::format_json(value, format_args.indent_length);
+    raw: &str,
+    file_path: &Path,
+    stdin: bool,
+    reporter: &mut Reporter,
+) -> Result<(), Failure> {
+    if format_args.check || format_args.diff {
+        if formatted != raw {
+            if format_args.check {
+                println!("{}", file_path.display());
+            }
+
+            if format_args.diff {
+                let color = color::enabled(format_args.color, std::io::stdout());
 
-    if format_args.write {
+                print!("{}", diff::unified_diff(raw, formatted, file_path, color));
+            }
+
+            return Err(Failure::Invalid);
+        }
+    } else if format_args.write && !stdin {
         if let Err(err) = std::fs::write(file_path, formatted) {
-            eprintln!("Unable to write to file, reason: {}", err);
-            return Err(());
+            reporter.error(
+                &format!("Unable to write to file, reason: {}", err),
+                Some(file_path),
+            );
+            return Err(Failure::Error);
         }
     } else {
-        println!("{}", formatted);
+        print!("{}", formatted);
     }
 
     Ok(())
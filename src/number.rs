@@ -0,0 +1,273 @@
+use std::{borrow::Cow, fmt};
+
+/// A JSON number kept as its original lexeme, so formatting never changes it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Number<'a> {
+    lexeme: Cow<'a, str>,
+}
+
+/// Arbitrary precision value of a number, equal to `digits * 10^exponent`,
+/// negated if `negative` is set. `digits` never has leading or trailing zeros,
+/// except for zero itself which is `"0"`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decimal {
+    pub negative: bool,
+    pub digits: String,
+    pub exponent: i64,
+}
+
+impl<'a> Number<'a> {
+    pub fn new(lexeme: &'a str) -> Number<'a> {
+        Number {
+            lexeme: Cow::Borrowed(lexeme),
+        }
+    }
+
+    pub fn from_f64(number: f64) -> Option<Number<'a>> {
+        match number.is_finite() {
+            true => Some(Number {
+                lexeme: Cow::Owned(number.to_string()),
+            }),
+            false => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.lexeme
+    }
+
+    pub fn as_decimal(&self) -> Option<Decimal> {
+        Decimal::parse(&self.lexeme)
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self.lexeme.parse::<f64>() {
+            Ok(number) if number.is_finite() => Some(number),
+            Ok(_) => None,
+            Err(_) => self.as_decimal()?.to_string().parse().ok(),
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        self.as_decimal()?.integer_string()?.parse().ok()
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        self.as_decimal()?.integer_string()?.parse().ok()
+    }
+}
+
+impl From<i64> for Number<'_> {
+    fn from(number: i64) -> Self {
+        Number {
+            lexeme: Cow::Owned(number.to_string()),
+        }
+    }
+}
+
+impl From<u64> for Number<'_> {
+    fn from(number: u64) -> Self {
+        Number {
+            lexeme: Cow::Owned(number.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Number<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.lexeme)
+    }
+}
+
+fn split_digits(s: &str) -> (&str, &str) {
+    let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    s.split_at(end)
+}
+
+impl Decimal {
+    fn parse(lexeme: &str) -> Option<Decimal> {
+        let (negative, rest) = match lexeme.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, lexeme.strip_prefix('+').unwrap_or(lexeme)),
+        };
+
+        if let Some(hex) = rest.strip_prefix("0x").or(rest.strip_prefix("0X")) {
+            if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                return None;
+            }
+
+            let value = u128::from_str_radix(hex, 16).ok()?;
+
+            return Decimal::parse(&value.to_string()).map(|decimal| Decimal {
+                negative,
+                ..decimal
+            });
+        }
+
+        let (integer, rest) = split_digits(rest);
+
+        let (fraction, rest) = match rest.strip_prefix('.') {
+            Some(rest) => split_digits(rest),
+            None => ("", rest),
+        };
+
+        if integer.is_empty() && fraction.is_empty() {
+            return None;
+        }
+
+        let exponent: i64 = match rest.strip_prefix(['e', 'E']) {
+            Some(rest) => {
+                let (sign, digits) = match rest.strip_prefix('-') {
+                    Some(digits) => (-1, digits),
+                    None => (1, rest.strip_prefix('+').unwrap_or(rest)),
+                };
+
+                if digits.is_empty() || !split_digits(digits).1.is_empty() {
+                    return None;
+                }
+
+                sign * digits.parse::<i64>().ok()?
+            }
+            None if rest.is_empty() => 0,
+            None => return None,
+        };
+
+        let all_digits = format!("{}{}", integer, fraction);
+        let significant = all_digits.trim_start_matches('0');
+        let trimmed = significant.trim_end_matches('0');
+
+        if trimmed.is_empty() {
+            return Some(Decimal {
+                negative,
+                digits: "0".to_string(),
+                exponent: 0,
+            });
+        }
+
+        let trailing_zeros = (significant.len() - trimmed.len()) as i64;
+
+        Some(Decimal {
+            negative,
+            digits: trimmed.to_string(),
+            exponent: exponent
+                .checked_sub(fraction.len() as i64)?
+                .checked_add(trailing_zeros)?,
+        })
+    }
+
+    fn integer_string(&self) -> Option<String> {
+        let length = (self.digits.len() as i64).checked_add(self.exponent)?;
+
+        if self.exponent < 0 || length > 20 {
+            return None;
+        }
+
+        let sign = match self.negative && self.digits != "0" {
+            true => "-",
+            false => "",
+        };
+
+        Some(format!(
+            "{}{}{}",
+            sign,
+            self.digits,
+            "0".repeat(self.exponent as usize)
+        ))
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.negative {
+            write!(f, "-")?;
+        }
+
+        match self.exponent {
+            0 => write!(f, "{}", self.digits),
+            exponent => write!(f, "{}e{}", self.digits, exponent),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decimal() {
+        let cases = vec![
+            ("0", Some((false, "0", 0))),
+            ("-0.000", Some((true, "0", 0))),
+            ("1.0", Some((false, "1", 0))),
+            ("1e3", Some((false, "1", 3))),
+            ("1200", Some((false, "12", 2))),
+            ("-12.345e2", Some((true, "12345", -1))),
+            ("0.0012", Some((false, "12", -4))),
+            (
+                "12345678901234567890123",
+                Some((false, "12345678901234567890123", 0)),
+            ),
+            ("1E+2", Some((false, "1", 2))),
+            ("1e", None),
+            ("e5", None),
+            ("1.2.3", None),
+            ("inf", None),
+            ("-0x1F", Some((true, "31", 0))),
+            ("0xA00", Some((false, "256", 1))),
+            ("1e9223372036854775807", Some((false, "1", i64::MAX))),
+            ("10e9223372036854775807", None),
+            ("1.5e-9223372036854775808", None),
+        ];
+
+        for (lexeme, expected) in cases {
+            let expected = expected.map(|(negative, digits, exponent)| Decimal {
+                negative,
+                digits: digits.to_string(),
+                exponent,
+            });
+
+            assert_eq!(Number::new(lexeme).as_decimal(), expected, "{}", lexeme);
+        }
+    }
+
+    #[test]
+    fn test_conversions() {
+        let id = Number::new("12345678901234567890");
+        assert_eq!(id.as_u64(), Some(12345678901234567890));
+        assert_eq!(id.as_i64(), None);
+        assert_eq!(id.to_string(), "12345678901234567890");
+
+        let cases = vec![
+            ("1.0", Some(1), Some(1), Some(1.0)),
+            ("1e3", Some(1000), Some(1000), Some(1000.0)),
+            ("-5", Some(-5), None, Some(-5.0)),
+            ("-0", Some(0), Some(0), Some(-0.0)),
+            ("1.5", None, None, Some(1.5)),
+            ("1e400", None, None, None),
+            ("1e9223372036854775807", None, None, None),
+            ("-1e-9223372036854775807", None, None, Some(-0.0)),
+            ("0xff", Some(255), Some(255), Some(255.0)),
+            (".5", None, None, Some(0.5)),
+            (
+                "-9223372036854775808",
+                Some(i64::MIN),
+                None,
+                Some(-9.223372036854776e18),
+            ),
+            (
+                "18446744073709551616",
+                None,
+                None,
+                Some(1.8446744073709552e19),
+            ),
+        ];
+
+        for (lexeme, as_i64, as_u64, as_f64) in cases {
+            let number = Number::new(lexeme);
+
+            assert_eq!(number.as_i64(), as_i64, "{}", lexeme);
+            assert_eq!(number.as_u64(), as_u64, "{}", lexeme);
+            assert_eq!(number.as_f64(), as_f64, "{}", lexeme);
+        }
+    }
+}
@@ -1,17 +1,19 @@
-use crate::lexer::{Token, TokenType};
-use std::collections::BTreeMap;
+use crate::diagnostic;
+use crate::lexer::{Dialect, Token, TokenType};
+use crate::number::Number;
+use std::{borrow::Cow, collections::HashMap, error::Error, fmt};
 
 #[derive(Debug, PartialEq)]
 pub enum Json<'a> {
     Null,
     Bool(bool),
-    Number(f64),
-    String(&'a str),
-    Object(BTreeMap<&'a str, Json<'a>>),
+    Number(Number<'a>),
+    String(Cow<'a, str>),
+    Object(Vec<(Cow<'a, str>, Json<'a>)>),
     Array(Vec<Json<'a>>),
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ParseErrorType {
     UnexpectedEnd,
     UnexpectedToken,
@@ -21,6 +23,73 @@ pub enum ParseErrorType {
     MissingColon,
     MissingCloseCurly,
     MissingCloseSquare,
+    InvalidNumber,
+    InvalidString,
+    UnterminatedString,
+    TrailingContent,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SuggestionType {
+    InsertComma,
+    RemoveTrailingComma,
+    QuoteKey,
+    InsertColon,
+    InsertCloseCurly,
+    InsertCloseSquare,
+}
+
+/// Replace the text between the `start` and `end` offsets with `replacement`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Edit {
+    pub start: usize,
+    pub end: usize,
+    pub replacement: String,
+}
+
+/// A likely fix for an error, `token` is the token the fix edits or inserts
+/// text after.
+#[derive(Debug, PartialEq)]
+pub struct Suggestion<'a> {
+    pub suggestion_type: SuggestionType,
+    pub token: &'a Token<'a>,
+}
+
+impl<'a> Suggestion<'a> {
+    pub fn new(suggestion_type: SuggestionType, token: &'a Token<'a>) -> Suggestion<'a> {
+        Suggestion {
+            suggestion_type,
+            token,
+        }
+    }
+
+    /// The edit to `source`, the text the tokens were lexed from.
+    pub fn edit(&self, source: &str) -> Edit {
+        let (start, end) = (self.token.offset, self.token.end(source));
+
+        let insert = |text: &str| Edit {
+            start: end,
+            end,
+            replacement: text.to_string(),
+        };
+
+        match self.suggestion_type {
+            SuggestionType::InsertComma => insert(","),
+            SuggestionType::InsertColon => insert(":"),
+            SuggestionType::InsertCloseCurly => insert("}"),
+            SuggestionType::InsertCloseSquare => insert("]"),
+            SuggestionType::RemoveTrailingComma => Edit {
+                start,
+                end,
+                replacement: String::new(),
+            },
+            SuggestionType::QuoteKey => Edit {
+                start,
+                end,
+                replacement: format!("\"{}\"", source[start..end].replace('\\', "\\\\")),
+            },
+        }
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -28,6 +97,10 @@ pub struct ParseError<'a> {
     pub error_type: ParseErrorType,
     pub token: Option<&'a Token<'a>>,
     pub expected: Option<&'a TokenType<'a>>,
+    /// Another token the error refers to, like the first occurrence of a
+    /// duplicate key or the bracket that was never closed
+    pub related: Option<&'a Token<'a>>,
+    pub suggestion: Option<Suggestion<'a>>,
 }
 
 impl<'a> ParseError<'a> {
@@ -40,6 +113,59 @@ impl<'a> ParseError<'a> {
             error_type,
             token,
             expected,
+            related: None,
+            suggestion: None,
+        }
+    }
+
+    pub fn with_related(mut self, related: &'a Token<'a>) -> ParseError<'a> {
+        self.related = Some(related);
+        self
+    }
+
+    pub fn with_suggestion(
+        mut self,
+        suggestion_type: SuggestionType,
+        token: &'a Token<'a>,
+    ) -> ParseError<'a> {
+        self.suggestion = Some(Suggestion::new(suggestion_type, token));
+        self
+    }
+
+    pub fn position(&self) -> Option<(usize, usize)> {
+        self.token.map(|token| (token.line, token.col))
+    }
+}
+
+impl fmt::Display for ParseError<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.position() {
+            Some((line, col)) => write!(
+                f,
+                "{}:{}: {}",
+                line + 1,
+                col + 1,
+                diagnostic::get_message(self)
+            ),
+            None => write!(f, "{}", diagnostic::get_message(self)),
+        }
+    }
+}
+
+impl Error for ParseError<'_> {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseOptions {
+    /// Parsing stops after this many errors, 0 for no limit
+    pub max_errors: usize,
+    pub dialect: Dialect,
+}
+
+impl Default for ParseOptions {
+    fn default() -> ParseOptions {
+        ParseOptions {
+            max_errors: 20,
+            dialect: Dialect::Json,
         }
     }
 }
@@ -64,205 +190,489 @@ impl<'a> ParseContext<'a> {
     }
 }
 
-fn expect<'a>(
-    token_type: &'a TokenType<'a>,
-    error_type: ParseErrorType,
-    tokens: &'a [Token],
-    i: usize,
-) -> Result<&'a Token<'a>, ParseError<'a>> {
-    match tokens.get(i) {
-        Some(token) => {
-            if token.token_type == *token_type {
-                Ok(token)
-            } else {
-                Err(ParseError::new(error_type, Some(token), Some(token_type)))
-            }
+enum Failure {
+    Recover(usize),
+    Abort,
+}
+
+enum Sync {
+    Resume(usize),
+    End(usize),
+}
+
+struct Parser<'a> {
+    tokens: Vec<&'a Token<'a>>,
+    errors: Vec<ParseError<'a>>,
+    max_errors: usize,
+    dialect: Dialect,
+}
+
+impl<'a> Parser<'a> {
+    fn new(tokens: &'a [Token<'a>], options: &ParseOptions) -> Parser<'a> {
+        let tokens = tokens
+            .iter()
+            .filter(|token| !matches!(token.token_type, TokenType::Comment(_)))
+            .collect();
+
+        Parser {
+            tokens,
+            errors: vec![],
+            max_errors: options.max_errors,
+            dialect: options.dialect,
+        }
+    }
+
+    fn error(&mut self, parse_error: ParseError<'a>) -> Result<(), Failure> {
+        self.errors.push(parse_error);
+
+        match self.max_errors != 0 && self.errors.len() >= self.max_errors {
+            true => Err(Failure::Abort),
+            false => Ok(()),
+        }
+    }
+
+    // An unterminated string that runs to the end of the file was already
+    // reported, and it is why the file ends too early
+    fn ends_in_string(&self) -> bool {
+        matches!(
+            self.tokens.last().map(|token| &token.token_type),
+            Some(TokenType::UnterminatedString(_, true))
+        )
+    }
+
+    fn unexpected_end(&mut self) -> Failure {
+        if !self.ends_in_string() {
+            self.errors
+                .push(ParseError::new(ParseErrorType::UnexpectedEnd, None, None));
+        }
+
+        Failure::Abort
+    }
+
+    fn get(&mut self, i: usize) -> Result<&'a Token<'a>, Failure> {
+        match self.tokens.get(i) {
+            Some(token) => Ok(*token),
+            None => Err(self.unexpected_end()),
         }
-        None => Err(ParseError::new(ParseErrorType::UnexpectedEnd, None, None)),
+    }
+
+    fn reported(&self, token: &Token) -> bool {
+        self.errors
+            .iter()
+            .any(|parse_error| parse_error.token.is_some_and(|t| std::ptr::eq(t, token)))
+    }
+
+    fn starts_key(&self, i: usize) -> bool {
+        let starts_line = i == 0 || self.tokens[i - 1].line < self.tokens[i].line;
+
+        let is_key = matches!(self.tokens[i].token_type, TokenType::String(_))
+            && matches!(
+                self.tokens.get(i + 1).map(|token| &token.token_type),
+                Some(TokenType::Colon)
+            );
+
+        starts_line && is_key
     }
 }
 
-fn check_trailing_comma<'a>(
-    tokens: &'a [Token],
-    last_comma: Option<usize>,
-    i: usize,
-) -> Result<usize, ParseError<'a>> {
-    match last_comma {
-        Some(index) => {
-            if index == i - 1 {
-                Err(ParseError::new(
-                    ParseErrorType::TrailingComma,
-                    tokens.get(index),
-                    None,
-                ))
-            } else {
-                Ok(i)
+fn expect_colon(parser: &mut Parser, i: usize) -> Result<(), Failure> {
+    let token = parser.get(i)?;
+
+    if token.token_type == TokenType::Colon {
+        return Ok(());
+    }
+
+    let key = parser.tokens[i - 1];
+
+    parser.error(
+        ParseError::new(
+            ParseErrorType::MissingColon,
+            Some(token),
+            Some(&TokenType::Colon),
+        )
+        .with_suggestion(SuggestionType::InsertColon, key),
+    )?;
+    Err(Failure::Recover(i))
+}
+
+fn synchronize(
+    parser: &mut Parser,
+    start: usize,
+    close: &TokenType,
+    keys: bool,
+) -> Result<Sync, Failure> {
+    let mut depth = 0;
+    let mut i = start;
+
+    loop {
+        let token = parser.get(i)?;
+
+        match token.token_type {
+            TokenType::OpenCurly | TokenType::OpenSquare => depth += 1,
+            TokenType::CloseCurly | TokenType::CloseSquare if depth > 0 => depth -= 1,
+            ref token_type if token_type == close => return Ok(Sync::End(i + 1)),
+            TokenType::CloseCurly | TokenType::CloseSquare => return Ok(Sync::End(i)),
+            TokenType::Comma if depth == 0 => return Ok(Sync::Resume(i)),
+            TokenType::String(_) if depth == 0 && keys && parser.starts_key(i) => {
+                return Ok(Sync::Resume(i))
             }
+            _ => {}
         }
-        None => Ok(i),
+
+        i += 1;
     }
 }
 
+fn after_comma(parser: &mut Parser, i: usize, close: &TokenType) -> Result<Option<usize>, Failure> {
+    let next = parser.get(i + 1)?;
+
+    if next.token_type != *close {
+        return Ok(None);
+    }
+
+    if parser.dialect != Dialect::Json {
+        return Ok(Some(i + 2));
+    }
+
+    let comma = parser.tokens[i];
+
+    parser.error(
+        ParseError::new(ParseErrorType::TrailingComma, Some(comma), None)
+            .with_suggestion(SuggestionType::RemoveTrailingComma, comma),
+    )?;
+
+    Ok(Some(i + 2))
+}
+
+// Tokens that most likely start another member when they follow one without
+// a comma in between
+fn starts_value(token_type: &TokenType) -> bool {
+    !matches!(
+        token_type,
+        TokenType::Colon | TokenType::Comma | TokenType::CloseCurly | TokenType::CloseSquare
+    )
+}
+
 fn for_each_comma<'a, G, B>(
+    parser: &mut Parser<'a>,
+    getter: G,
+    builder: B,
+    close: &'a TokenType<'a>,
+    missing_close: ParseErrorType,
+    start: usize,
+) -> Result<usize, Failure>
+where
+    G: Fn(&mut Parser<'a>, usize) -> Result<ParseContext<'a>, Failure>,
+    B: FnMut(&mut Parser<'a>, ParseContext<'a>, &'a Token<'a>) -> Result<(), Failure>,
+{
+    let result = for_each_item(parser, getter, builder, close, missing_close, start);
+
+    // The innermost unclosed container is the one the file ended in
+    if let Some(parse_error) = parser.errors.last_mut() {
+        if result.is_err()
+            && parse_error.error_type == ParseErrorType::UnexpectedEnd
+            && parse_error.related.is_none()
+        {
+            parse_error.related = Some(parser.tokens[start - 1]);
+        }
+    }
+
+    result
+}
+
+fn for_each_item<'a, G, B>(
+    parser: &mut Parser<'a>,
     getter: G,
     mut builder: B,
-    tokens: &'a Vec<Token>,
+    close: &'a TokenType<'a>,
+    missing_close: ParseErrorType,
     start: usize,
-) -> Result<usize, ParseError<'a>>
+) -> Result<usize, Failure>
 where
-    G: Fn(&'a Vec<Token>, usize) -> Result<ParseContext<'a>, ParseError<'a>>,
-    B: FnMut(ParseContext<'a>, Option<&'a Token<'a>>) -> Result<(), ParseError<'a>>,
+    G: Fn(&mut Parser<'a>, usize) -> Result<ParseContext<'a>, Failure>,
+    B: FnMut(&mut Parser<'a>, ParseContext<'a>, &'a Token<'a>) -> Result<(), Failure>,
 {
+    let keys = missing_close == ParseErrorType::MissingCloseCurly;
+    let insert_close = match keys {
+        true => SuggestionType::InsertCloseCurly,
+        false => SuggestionType::InsertCloseSquare,
+    };
+    let open = parser.tokens[start - 1];
     let mut i = start;
-    let mut last_comma: Option<usize> = None;
+
+    if parser.get(i)?.token_type == *close {
+        return Ok(i + 1);
+    }
 
     loop {
-        match getter(tokens, i) {
+        let sync = match getter(parser, i) {
             Ok(parse_context) => {
                 let next = parse_context.next;
 
-                builder(parse_context, tokens.get(i))?;
+                builder(parser, parse_context, parser.tokens[i])?;
 
-                i = next;
+                let previous = parser.tokens[next - 1];
 
-                match expect(
-                    &TokenType::Comma,
-                    ParseErrorType::UnexpectedToken,
-                    tokens,
-                    i,
-                ) {
-                    Ok(_) => {
-                        last_comma = Some(i);
-                        i += 1;
+                if next == parser.tokens.len() {
+                    if !parser.ends_in_string() {
+                        parser.errors.push(
+                            ParseError::new(ParseErrorType::UnexpectedEnd, None, None)
+                                .with_suggestion(insert_close, previous),
+                        );
+                    }
+
+                    return Err(Failure::Abort);
+                }
+
+                let token = parser.tokens[next];
+
+                match token.token_type {
+                    TokenType::Comma => Sync::Resume(next),
+                    ref token_type if token_type == close => return Ok(next + 1),
+                    // The comma was most likely swallowed by the unterminated string
+                    _ if matches!(
+                        parser.tokens[next - 1].token_type,
+                        TokenType::UnterminatedString(..)
+                    ) =>
+                    {
+                        Sync::Resume(next)
+                    }
+                    _ => {
+                        let suggestion_type = match starts_value(&token.token_type) {
+                            true => SuggestionType::InsertComma,
+                            false => insert_close,
+                        };
+
+                        parser.error(
+                            ParseError::new(missing_close, Some(token), Some(close))
+                                .with_related(open)
+                                .with_suggestion(suggestion_type, previous),
+                        )?;
+                        synchronize(parser, next, close, keys)?
                     }
-                    Err(_) => break,
                 }
             }
-            Err(parse_error) => match parse_error.error_type {
-                ParseErrorType::UnexpectedToken => break,
-                _ => return Err(parse_error),
+            Err(Failure::Recover(at)) => synchronize(parser, at, close, keys)?,
+            Err(Failure::Abort) => return Err(Failure::Abort),
+        };
+
+        i = match sync {
+            Sync::End(next) => return Ok(next),
+            Sync::Resume(next) if parser.tokens[next].token_type != TokenType::Comma => next,
+            Sync::Resume(comma) => match after_comma(parser, comma, close)? {
+                Some(next) => return Ok(next),
+                None => comma + 1,
             },
-        }
+        };
     }
-
-    check_trailing_comma(tokens, last_comma, i)
 }
 
-fn expect_key<'a>(tokens: &'a [Token], i: usize) -> Result<&'a str, ParseError<'a>> {
-    match tokens.get(i) {
-        Some(token) => match token.token_type {
-            TokenType::String(s) => Ok(s),
-            TokenType::Invalid(_) | TokenType::Number(_) | TokenType::Bool(_) => Err(
-                ParseError::new(ParseErrorType::KeyNotInQuotes, Some(token), None),
-            ),
-            _ => Err(ParseError::new(ParseErrorType::UnexpectedToken, None, None)),
-        },
-        None => Err(ParseError::new(ParseErrorType::UnexpectedEnd, None, None)),
-    }
-}
+fn expect_key<'a>(parser: &mut Parser<'a>, i: usize) -> Result<&'a str, Failure> {
+    let token = parser.get(i)?;
 
-fn key_value_pair<'a>(
-    tokens: &'a Vec<Token>,
-    start: usize,
-) -> Result<ParseContext<'a>, ParseError<'a>> {
-    let key = match expect_key(tokens, start) {
-        Ok(k) => k,
-        Err(parse_error) => return Err(parse_error),
+    let json5 = parser.dialect == Dialect::Json5;
+
+    let error_type = match &token.token_type {
+        TokenType::String(s) => return Ok(s.as_ref()),
+        TokenType::Identifier(s) => return Ok(s),
+        TokenType::Null if json5 => return Ok("null"),
+        TokenType::Bool(true) if json5 => return Ok("true"),
+        TokenType::Bool(false) if json5 => return Ok("false"),
+        TokenType::Number(s) if json5 && s.chars().all(char::is_alphabetic) => return Ok(s),
+        TokenType::InvalidString(..) => ParseErrorType::InvalidString,
+        TokenType::UnterminatedString(..) => ParseErrorType::UnterminatedString,
+        TokenType::Invalid(_)
+        | TokenType::Number(_)
+        | TokenType::InvalidNumber(..)
+        | TokenType::Bool(_) => ParseErrorType::KeyNotInQuotes,
+        _ => ParseErrorType::UnexpectedToken,
     };
 
-    expect(
-        &TokenType::Colon,
-        ParseErrorType::MissingColon,
-        tokens,
-        start + 1,
-    )?;
+    let parse_error = match error_type {
+        ParseErrorType::KeyNotInQuotes => ParseError::new(error_type, Some(token), None)
+            .with_suggestion(SuggestionType::QuoteKey, token),
+        _ => ParseError::new(error_type, Some(token), None),
+    };
 
-    let value_parse_context = value(tokens, start + 2)?;
+    parser.error(parse_error)?;
+    Err(Failure::Recover(i))
+}
+
+fn key_value_pair<'a>(parser: &mut Parser<'a>, start: usize) -> Result<ParseContext<'a>, Failure> {
+    let key = expect_key(parser, start)?;
+
+    expect_colon(parser, start + 1)?;
 
-    return Ok(ParseContext::key_value_pair(
+    let value_parse_context = value(parser, start + 2)?;
+
+    Ok(ParseContext::key_value_pair(
         key,
         value_parse_context.value,
         value_parse_context.next,
-    ));
+    ))
 }
 
-fn object<'a>(tokens: &'a Vec<Token>, start: usize) -> Result<ParseContext<'a>, ParseError<'a>> {
-    let mut object = BTreeMap::new();
-    let builder = |parse_context: ParseContext<'a>, token: Option<&'a Token<'a>>| match object
-        .insert(parse_context.key, parse_context.value)
-    {
-        Some(_) => return Err(ParseError::new(ParseErrorType::DuplicateKey, token, None)),
-        None => Ok(()),
-    };
+fn object<'a>(parser: &mut Parser<'a>, start: usize) -> Result<ParseContext<'a>, Failure> {
+    let mut object = vec![];
+    let mut keys = HashMap::new();
+    let builder =
+        |parser: &mut Parser<'a>, parse_context: ParseContext<'a>, token: &'a Token<'a>| {
+            if let Some(&first) = keys.get(parse_context.key) {
+                return parser.error(
+                    ParseError::new(ParseErrorType::DuplicateKey, Some(token), None)
+                        .with_related(first),
+                );
+            }
 
-    let i = match for_each_comma(key_value_pair, builder, tokens, start + 1) {
-        Ok(next) => next,
-        Err(parse_error) => return Err(parse_error),
-    };
+            keys.insert(parse_context.key, token);
 
-    let value = Json::Object(object);
+            object.push((Cow::Borrowed(parse_context.key), parse_context.value));
+            Ok(())
+        };
 
-    match expect(
+    let next = for_each_comma(
+        parser,
+        key_value_pair,
+        builder,
         &TokenType::CloseCurly,
         ParseErrorType::MissingCloseCurly,
-        tokens,
-        i,
-    ) {
-        Ok(_) => Ok(ParseContext::new(value, i + 1)),
-        Err(parse_error) => Err(parse_error),
-    }
+        start + 1,
+    )?;
+
+    Ok(ParseContext::new(Json::Object(object), next))
 }
 
-fn array<'a>(tokens: &'a Vec<Token>, start: usize) -> Result<ParseContext<'a>, ParseError<'a>> {
+fn array<'a>(parser: &mut Parser<'a>, start: usize) -> Result<ParseContext<'a>, Failure> {
     let mut array = vec![];
-    let builder = |parse_context: ParseContext<'a>, _| {
+    let builder = |_: &mut Parser<'a>, parse_context: ParseContext<'a>, _| {
         array.push(parse_context.value);
         Ok(())
     };
 
-    let i = match for_each_comma(value, builder, tokens, start + 1) {
-        Ok(next) => next,
-        Err(parse_error) => return Err(parse_error),
-    };
-
-    let value = Json::Array(array);
-
-    match expect(
+    let next = for_each_comma(
+        parser,
+        value,
+        builder,
         &TokenType::CloseSquare,
         ParseErrorType::MissingCloseSquare,
-        tokens,
-        i,
-    ) {
-        Ok(_) => Ok(ParseContext::new(value, i + 1)),
-        Err(parse_error) => Err(parse_error),
-    }
+        start + 1,
+    )?;
+
+    Ok(ParseContext::new(Json::Array(array), next))
 }
 
-fn value<'a>(tokens: &'a Vec<Token>, start: usize) -> Result<ParseContext<'a>, ParseError<'a>> {
-    let start_token = match tokens.get(start) {
-        Some(token) => token,
-        None => return Err(ParseError::new(ParseErrorType::UnexpectedEnd, None, None)),
+fn value<'a>(parser: &mut Parser<'a>, start: usize) -> Result<ParseContext<'a>, Failure> {
+    let start_token = parser.get(start)?;
+
+    let error_type = match &start_token.token_type {
+        TokenType::Null => return Ok(ParseContext::new(Json::Null, start + 1)),
+        TokenType::Bool(x) => return Ok(ParseContext::new(Json::Bool(*x), start + 1)),
+        TokenType::Number(x) => {
+            return Ok(ParseContext::new(Json::Number(Number::new(x)), start + 1))
+        }
+        TokenType::String(x) => {
+            return Ok(ParseContext::new(
+                Json::String(Cow::Borrowed(x.as_ref())),
+                start + 1,
+            ))
+        }
+        TokenType::OpenCurly => return object(parser, start),
+        TokenType::OpenSquare => return array(parser, start),
+        TokenType::InvalidNumber(..) => ParseErrorType::InvalidNumber,
+        TokenType::InvalidString(..) => ParseErrorType::InvalidString,
+        TokenType::UnterminatedString(..) => ParseErrorType::UnterminatedString,
+        TokenType::Invalid(_) | TokenType::Identifier(_) => ParseErrorType::UnexpectedToken,
+        _ => {
+            parser.error(ParseError::new(
+                ParseErrorType::UnexpectedToken,
+                Some(start_token),
+                None,
+            ))?;
+
+            return Err(Failure::Recover(start));
+        }
     };
 
-    match start_token.token_type {
-        TokenType::Null => Ok(ParseContext::new(Json::Null, start + 1)),
-        TokenType::Bool(x) => Ok(ParseContext::new(Json::Bool(x), start + 1)),
-        TokenType::Number(x) => Ok(ParseContext::new(Json::Number(x), start + 1)),
-        TokenType::String(x) => Ok(ParseContext::new(Json::String(x), start + 1)),
-        TokenType::OpenCurly => object(tokens, start),
-        TokenType::OpenSquare => array(tokens, start),
-        _ => Err(ParseError::new(
-            ParseErrorType::UnexpectedToken,
-            Some(start_token),
-            None,
-        )),
+    parser.error(ParseError::new(error_type, Some(start_token), None))?;
+    Ok(ParseContext::new(Json::Null, start + 1))
+}
+
+fn next_line(tokens: &[&Token], i: usize) -> usize {
+    let line = tokens[i].line;
+
+    i + tokens[i..]
+        .iter()
+        .take_while(|token| token.line == line)
+        .count()
+}
+
+pub fn parse<'a>(tokens: &'a [Token<'a>]) -> Result<Json<'a>, Vec<ParseError<'a>>> {
+    parse_with(tokens, &ParseOptions::default())
+}
+
+pub fn parse_with<'a>(
+    tokens: &'a [Token<'a>],
+    options: &ParseOptions,
+) -> Result<Json<'a>, Vec<ParseError<'a>>> {
+    let mut parser = Parser::new(tokens, options);
+    let parsed = value(&mut parser, 0);
+
+    if let Ok(parse_context) = &parsed {
+        if let Some(token) = parser.tokens.get(parse_context.next).copied() {
+            if !parser.reported(token) {
+                let _ = parser.error(ParseError::new(
+                    ParseErrorType::TrailingContent,
+                    Some(token),
+                    None,
+                ));
+            }
+        }
+    }
+
+    match parsed {
+        Ok(parse_context) if parser.errors.is_empty() => Ok(parse_context.value),
+        _ => Err(parser.errors),
     }
 }
 
-pub fn parse<'a>(tokens: &'a Vec<Token>) -> Result<Json<'a>, ParseError<'a>> {
-    match value(tokens, 0) {
-        Ok(parse_context) => Ok(parse_context.value),
-        Err(parse_error) => Err(parse_error),
+pub fn parse_lines<'a>(tokens: &'a [Token<'a>]) -> Result<Vec<Json<'a>>, Vec<ParseError<'a>>> {
+    parse_lines_with(tokens, &ParseOptions::default())
+}
+
+pub fn parse_lines_with<'a>(
+    tokens: &'a [Token<'a>],
+    options: &ParseOptions,
+) -> Result<Vec<Json<'a>>, Vec<ParseError<'a>>> {
+    let mut parser = Parser::new(tokens, options);
+    let mut values = vec![];
+    let mut i = 0;
+
+    while let Some(token) = parser.tokens.get(i).copied() {
+        if i != 0 && token.line == parser.tokens[i - 1].line {
+            let trailing_content =
+                ParseError::new(ParseErrorType::TrailingContent, Some(token), None);
+
+            match parser.error(trailing_content) {
+                Ok(()) => i = next_line(&parser.tokens, i),
+                Err(_) => break,
+            }
+
+            continue;
+        }
+
+        match value(&mut parser, i) {
+            Ok(parse_context) => {
+                values.push(parse_context.value);
+                i = parse_context.next;
+            }
+            Err(Failure::Recover(at)) => i = next_line(&parser.tokens, at),
+            Err(Failure::Abort) => break,
+        }
+    }
+
+    match parser.errors.is_empty() {
+        true => Ok(values),
+        false => Err(parser.errors),
     }
 }
 
@@ -280,11 +690,11 @@ mod tests {
             let tokens = lexer::lex(raw);
             let value = parse(&tokens);
 
-            let expected = Err(ParseError::new(
+            let expected = Err(vec![ParseError::new(
                 ParseErrorType::UnexpectedToken,
                 Some(&tokens[0]),
                 None,
-            ));
+            )]);
 
             assert_eq!(value, expected);
         }
@@ -292,8 +702,8 @@ mod tests {
 
     fn assert_case<'a>(
         case: &'static str,
-        actual: Result<Json<'a>, ParseError<'a>>,
-        expected: Result<Json<'a>, ParseError<'a>>,
+        actual: Result<Json<'a>, Vec<ParseError<'a>>>,
+        expected: Result<Json<'a>, Vec<ParseError<'a>>>,
     ) {
         if actual != expected {
             panic!(
@@ -308,45 +718,41 @@ mod tests {
         let cases = vec![
             (
                 "",
-                Err(ParseError::new(ParseErrorType::UnexpectedEnd, None, None)),
-            ),
-            (
-                "[1  , 2",
-                Err(ParseError::new(ParseErrorType::UnexpectedEnd, None, None)),
-            ),
-            (
-                "[1, 2,  ",
-                Err(ParseError::new(ParseErrorType::UnexpectedEnd, None, None)),
+                Err(vec![ParseError::new(
+                    ParseErrorType::UnexpectedEnd,
+                    None,
+                    None,
+                )]),
             ),
             ("null  ", Ok(Json::Null)),
             ("true", Ok(Json::Bool(true))),
             ("  false ", Ok(Json::Bool(false))),
-            (" 1234", Ok(Json::Number(1234.0))),
-            ("\"foo\"", Ok(Json::String("foo"))),
+            (" 1234", Ok(Json::Number(Number::new("1234")))),
+            ("\"foo\"", Ok(Json::String("foo".into()))),
             (
                 "{\"foo\":{   \"bar\":1234}   }",
-                Ok(Json::Object(BTreeMap::from([(
-                    "foo",
-                    Json::Object(BTreeMap::from([("bar", Json::Number(1234.0))])),
-                )]))),
+                Ok(Json::Object(vec![(
+                    "foo".into(),
+                    Json::Object(vec![("bar".into(), Json::Number(Number::new("1234")))]),
+                )])),
             ),
             (
                 "{\"foo\":{   \"bar\":1234},  \"another\": \"testing\" }",
-                Ok(Json::Object(BTreeMap::from([
+                Ok(Json::Object(vec![
                     (
-                        "foo",
-                        Json::Object(BTreeMap::from([("bar", Json::Number(1234.0))])),
+                        "foo".into(),
+                        Json::Object(vec![("bar".into(), Json::Number(Number::new("1234")))]),
                     ),
-                    ("another", Json::String("testing")),
-                ]))),
+                    ("another".into(), Json::String("testing".into())),
+                ])),
             ),
             (
                 "[1,   2,3  ,  4]",
                 Ok(Json::Array(vec![
-                    Json::Number(1.0),
-                    Json::Number(2.0),
-                    Json::Number(3.0),
-                    Json::Number(4.0),
+                    Json::Number(Number::new("1")),
+                    Json::Number(Number::new("2")),
+                    Json::Number(Number::new("3")),
+                    Json::Number(Number::new("4")),
                 ])),
             ),
         ];
@@ -400,6 +806,24 @@ mod tests {
                 None,
             ),
             ("[1, 2, 3,]", ParseErrorType::TrailingComma, 6, None),
+            ("[1, 2, 03]", ParseErrorType::InvalidNumber, 5, None),
+            ("{\"foo\": -.5}", ParseErrorType::InvalidNumber, 3, None),
+            ("[\"a\", \"b\\qc\"]", ParseErrorType::InvalidString, 3, None),
+            ("{\"\\ud800\": 1}", ParseErrorType::InvalidString, 1, None),
+            (
+                "{\"a\": 1} {\"b\": 2}",
+                ParseErrorType::TrailingContent,
+                5,
+                None,
+            ),
+            ("[1] xyz", ParseErrorType::TrailingContent, 3, None),
+            ("1 2", ParseErrorType::TrailingContent, 1, None),
+            (
+                "{\"a\": 1, \"\\u0061\": 2}",
+                ParseErrorType::DuplicateKey,
+                5,
+                None,
+            ),
             (
                 "{\"foo\":123, \"foo\": 432}",
                 ParseErrorType::DuplicateKey,
@@ -430,15 +854,344 @@ mod tests {
             let (raw, expected_error, token_location, expected_token_type) = case;
 
             let tokens = lexer::lex(raw);
-            let expected = Err(ParseError::new(
+            let expected = Err(vec![ParseError::new(
                 expected_error,
                 Some(tokens.get(token_location).unwrap()),
                 expected_token_type,
-            ));
+            )]);
 
-            let value = parse(&tokens);
+            // Related tokens and suggestions are covered by their own tests
+            let value = parse(&tokens).map_err(|errors| {
+                errors
+                    .into_iter()
+                    .map(|error| ParseError {
+                        related: None,
+                        suggestion: None,
+                        ..error
+                    })
+                    .collect()
+            });
 
             assert_case(raw, value, expected)
         }
     }
+
+    #[test]
+    fn test_parse_related() {
+        let cases: Vec<(&str, ParseErrorType, usize, usize)> = vec![
+            (
+                "{\"a\": 1, \"b\": 2, \"a\": 3}",
+                ParseErrorType::DuplicateKey,
+                9,
+                1,
+            ),
+            (
+                "{\"a\": 1, \"a\": 2, \"a\": 3}",
+                ParseErrorType::DuplicateKey,
+                5,
+                1,
+            ),
+            (
+                "{\"a\": 1 \"b\": 2}",
+                ParseErrorType::MissingCloseCurly,
+                4,
+                0,
+            ),
+            ("{\"a\": [1 2]}", ParseErrorType::MissingCloseSquare, 5, 3),
+        ];
+
+        for (raw, error_type, token, related) in cases {
+            let tokens = lexer::lex(raw);
+            let errors = parse(&tokens).unwrap_err();
+
+            assert_eq!(errors[0].error_type, error_type, "{}", raw);
+            assert_eq!(errors[0].token, Some(&tokens[token]), "{}", raw);
+            assert_eq!(errors[0].related, Some(&tokens[related]), "{}", raw);
+        }
+    }
+
+    #[test]
+    fn test_parse_unclosed() {
+        let cases = [
+            ("{\"a\": [1, 2", 3),
+            ("[1, 2,  ", 0),
+            ("{\"a\": {", 3),
+            ("[{", 1),
+        ];
+
+        for (raw, related) in cases {
+            let tokens = lexer::lex(raw);
+            let errors = parse(&tokens).unwrap_err();
+
+            assert_eq!(errors.len(), 1, "{}", raw);
+            assert_eq!(
+                errors[0].error_type,
+                ParseErrorType::UnexpectedEnd,
+                "{}",
+                raw
+            );
+            assert_eq!(errors[0].token, None, "{}", raw);
+            assert_eq!(errors[0].related, Some(&tokens[related]), "{}", raw);
+        }
+    }
+
+    #[test]
+    fn test_parse_lines() {
+        let raw = "{\"a\": 1}\n\n[true,\nnull]\n\"foo\"\n";
+        let tokens = lexer::lex(raw);
+
+        assert_eq!(
+            parse_lines(&tokens),
+            Ok(vec![
+                Json::Object(vec![("a".into(), Json::Number(Number::new("1")))]),
+                Json::Array(vec![Json::Bool(true), Json::Null]),
+                Json::String("foo".into()),
+            ])
+        );
+
+        assert_eq!(parse_lines(&lexer::lex("")), Ok(vec![]));
+
+        let tokens = lexer::lex("{\"a\": 1}\n[1] [2]\n");
+
+        assert_eq!(
+            parse_lines(&tokens),
+            Err(vec![ParseError::new(
+                ParseErrorType::TrailingContent,
+                Some(&tokens[8]),
+                None
+            )])
+        );
+    }
+
+    #[test]
+    fn test_parse_suggestions() {
+        let cases = vec![
+            ("[1 2]", "[1, 2]"),
+            ("[1  , 2", "[1  , 2]"),
+            ("{\"a\": [1}", "{\"a\": [1]}"),
+            ("{\"a\": {\"b\": 1 :", "{\"a\": {\"b\": 1} :"),
+            ("[1, 2,]", "[1, 2]"),
+            ("{a\\b: 1}", "{\"a\\\\b\": 1}"),
+            ("{\"a\": 1, true: 2}", "{\"a\": 1, \"true\": 2}"),
+            ("{\"a\" 1}", "{\"a\": 1}"),
+            ("{\"\\u0061\"\n1}", "{\"\\u0061\":\n1}"),
+        ];
+
+        for (raw, fixed) in cases {
+            let tokens = lexer::lex(raw);
+            let errors = parse(&tokens).unwrap_err();
+            let edit = errors[0].suggestion.as_ref().unwrap().edit(raw);
+
+            let mut result = raw.to_string();
+            result.replace_range(edit.start..edit.end, &edit.replacement);
+
+            assert_eq!(result, fixed, "{}", raw);
+        }
+
+        let tokens = lexer::lex("[1, 2 3");
+        let errors = parse(&tokens).unwrap_err();
+
+        assert_eq!(
+            errors[0].suggestion,
+            Some(Suggestion::new(SuggestionType::InsertComma, &tokens[3]))
+        );
+    }
+
+    #[test]
+    fn test_parse_recovery() {
+        let raw = "{\n  \"a\": 1\n  \"b\": [1 2],\n  \"c\": 03,\n  \"a\": true,\n  \"d\": {\"e\" 1},\n  f: null,\n  \"g\": [1,],\n}";
+        let tokens = lexer::lex(raw);
+
+        let expected = vec![
+            ParseError::new(
+                ParseErrorType::MissingCloseCurly,
+                Some(&tokens[4]),
+                Some(&TokenType::CloseCurly),
+            )
+            .with_related(&tokens[0])
+            .with_suggestion(SuggestionType::InsertComma, &tokens[3]),
+            ParseError::new(
+                ParseErrorType::MissingCloseSquare,
+                Some(&tokens[8]),
+                Some(&TokenType::CloseSquare),
+            )
+            .with_related(&tokens[6])
+            .with_suggestion(SuggestionType::InsertComma, &tokens[7]),
+            ParseError::new(ParseErrorType::InvalidNumber, Some(&tokens[13]), None),
+            ParseError::new(ParseErrorType::DuplicateKey, Some(&tokens[15]), None)
+                .with_related(&tokens[1]),
+            ParseError::new(
+                ParseErrorType::MissingColon,
+                Some(&tokens[23]),
+                Some(&TokenType::Colon),
+            )
+            .with_suggestion(SuggestionType::InsertColon, &tokens[22]),
+            ParseError::new(ParseErrorType::KeyNotInQuotes, Some(&tokens[26]), None)
+                .with_suggestion(SuggestionType::QuoteKey, &tokens[26]),
+            ParseError::new(ParseErrorType::TrailingComma, Some(&tokens[34]), None)
+                .with_suggestion(SuggestionType::RemoveTrailingComma, &tokens[34]),
+            ParseError::new(ParseErrorType::TrailingComma, Some(&tokens[36]), None)
+                .with_suggestion(SuggestionType::RemoveTrailingComma, &tokens[36]),
+        ];
+
+        assert_eq!(parse(&tokens), Err(expected));
+
+        let options = ParseOptions {
+            max_errors: 2,
+            ..ParseOptions::default()
+        };
+        let errors = parse_with(&tokens, &options).unwrap_err();
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[1].error_type, ParseErrorType::MissingCloseSquare);
+
+        let raw = format!("[{}1]", "03, ".repeat(30));
+        let tokens = lexer::lex(&raw);
+        let options = ParseOptions {
+            max_errors: 0,
+            ..ParseOptions::default()
+        };
+
+        assert_eq!(parse(&tokens).unwrap_err().len(), 20);
+        assert_eq!(parse_with(&tokens, &options).unwrap_err().len(), 30);
+    }
+
+    #[test]
+    fn test_parse_unterminated_string() {
+        let raw = "{\n  \"a\": \"b,\n  \"c\": [\"d\", \"e\n  ],\n  \"f: 1\n}";
+        let tokens = lexer::lex(raw);
+
+        let expected = vec![
+            ParseError::new(ParseErrorType::UnterminatedString, Some(&tokens[3]), None),
+            ParseError::new(ParseErrorType::UnterminatedString, Some(&tokens[9]), None),
+            ParseError::new(ParseErrorType::UnterminatedString, Some(&tokens[12]), None),
+        ];
+
+        assert_eq!(parse(&tokens), Err(expected));
+        assert_eq!(
+            parse(&tokens).unwrap_err()[0].to_string(),
+            "2:8: Unterminated string, expected a closing '\"' at 2:11, the end of the line"
+        );
+
+        let options = ParseOptions {
+            dialect: Dialect::Json5,
+            ..ParseOptions::default()
+        };
+        let cases = [
+            (
+                "[\"ab",
+                "1:2: Unterminated string, expected a closing '\"' at 1:5, the end of the file",
+            ),
+            (
+                "['ab\n]",
+                "1:2: Unterminated string, expected a closing ''' at 1:5, the end of the line",
+            ),
+            (
+                "['a\\\r\nbc",
+                "1:2: Unterminated string, expected a closing ''' at 2:3, the end of the file",
+            ),
+        ];
+
+        for (raw, expected) in cases {
+            let tokens = lexer::lex_with(raw, Dialect::Json5);
+            let errors = parse_with(&tokens, &options).unwrap_err();
+
+            assert_eq!(errors[0].to_string(), expected, "{}", raw);
+        }
+
+        // The string that runs to the end of the file is the only error
+        for raw in ["[\"\\u", "{\"a\": \"b", "{\"a", "\"a"] {
+            let tokens = lexer::lex(raw);
+            let errors = parse(&tokens).unwrap_err();
+
+            assert_eq!(errors.len(), 1, "{}", raw);
+            assert_eq!(
+                errors[0].error_type,
+                ParseErrorType::UnterminatedString,
+                "{}",
+                raw
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_dialects() {
+        let raw = "{\n  // comment\n  \"a\": [1, 2,],\n  \"b\": true, /* trailing */\n}";
+        let expected = Json::Object(vec![
+            (
+                "a".into(),
+                Json::Array(vec![
+                    Json::Number(Number::new("1")),
+                    Json::Number(Number::new("2")),
+                ]),
+            ),
+            ("b".into(), Json::Bool(true)),
+        ]);
+
+        let jsonc = ParseOptions {
+            dialect: Dialect::Jsonc,
+            ..ParseOptions::default()
+        };
+
+        let tokens = lexer::lex_with(raw, Dialect::Jsonc);
+        assert_eq!(parse_with(&tokens, &jsonc), Ok(expected));
+
+        let tokens = lexer::lex_with("{foo: 1}", Dialect::Jsonc);
+        assert_eq!(
+            parse_with(&tokens, &jsonc),
+            Err(vec![ParseError::new(
+                ParseErrorType::KeyNotInQuotes,
+                Some(&tokens[1]),
+                None
+            )
+            .with_suggestion(SuggestionType::QuoteKey, &tokens[1])])
+        );
+
+        let json5 = ParseOptions {
+            dialect: Dialect::Json5,
+            ..ParseOptions::default()
+        };
+
+        let raw = "{foo: 'bar', null: 0x10, Infinity: -Infinity, nested: [.5,],}";
+        let tokens = lexer::lex_with(raw, Dialect::Json5);
+        let expected = Json::Object(vec![
+            ("foo".into(), Json::String("bar".into())),
+            ("null".into(), Json::Number(Number::new("0x10"))),
+            ("Infinity".into(), Json::Number(Number::new("-Infinity"))),
+            (
+                "nested".into(),
+                Json::Array(vec![Json::Number(Number::new(".5"))]),
+            ),
+        ]);
+
+        assert_eq!(parse_with(&tokens, &json5), Ok(expected));
+
+        let tokens = lexer::lex_with("[foo]", Dialect::Json5);
+        assert_eq!(
+            parse_with(&tokens, &json5),
+            Err(vec![ParseError::new(
+                ParseErrorType::UnexpectedToken,
+                Some(&tokens[1]),
+                None
+            )])
+        );
+    }
+
+    #[test]
+    fn test_parse_lines_recovery() {
+        let raw = "{\"a\": 01}\n[1 2]\n]\n{\"ok\": true}\n";
+        let tokens = lexer::lex(raw);
+
+        let errors = parse_lines(&tokens).unwrap_err();
+        let error_types: Vec<ParseErrorType> = errors.iter().map(|e| e.error_type).collect();
+
+        assert_eq!(
+            error_types,
+            vec![
+                ParseErrorType::InvalidNumber,
+                ParseErrorType::MissingCloseSquare,
+                ParseErrorType::UnexpectedToken,
+            ]
+        );
+    }
 }
@@ -1,42 +1,412 @@
 use std::path::Path;
 
-use crate::{diagnostic, lexer, parser::ParseError};
+use color::{paint, BLUE, BOLD, BOLD_RED, CYAN, GREEN, RED};
+use joxide::{
+    diagnostic,
+    lexer::Token,
+    parser::{ParseError, Suggestion},
+};
+use unicode_width::UnicodeWidthChar;
 
-fn get_line(content: &str, line_number: usize) -> Option<&str> {
-    for (line_no, line) in content.split_terminator('\n').enumerate() {
-        if line_no == line_number {
-            return Some(line);
+// Lines shown around each labeled line
+const CONTEXT: usize = 1;
+
+struct Label<'a> {
+    token: &'a Token<'a>,
+    mark: char,
+    style: &'a str,
+    text: &'a str,
+}
+
+// Splits the same way the lexer counts lines, on "\n", "\r\n" and "\r"
+fn get_lines(content: &str) -> Vec<&str> {
+    let mut lines = vec![];
+    let mut rest = content;
+
+    while let Some(end) = rest.find(['\n', '\r']) {
+        lines.push(&rest[..end]);
+
+        rest = match rest[end..].starts_with("\r\n") {
+            true => &rest[end + 2..],
+            false => &rest[end + 1..],
+        };
+    }
+
+    if !rest.is_empty() {
+        lines.push(rest);
+    }
+
+    lines
+}
+
+fn get_padding(line: &str, col: usize) -> String {
+    line.chars()
+        .take(col)
+        .map(|c| match c {
+            '\t' => "\t".to_string(),
+            _ => " ".repeat(c.width().unwrap_or(0)),
+        })
+        .collect()
+}
+
+// The part of the token on its first line, at least one column wide
+fn get_underline(token: &Token, content: &str, mark: char) -> String {
+    let span = &content[token.offset..token.end(content)];
+    let width: usize = span
+        .chars()
+        .take_while(|c| *c != '\n' && *c != '\r')
+        .map(|c| c.width().unwrap_or(0))
+        .sum();
+
+    mark.to_string().repeat(width.max(1))
+}
+
+fn render_snippet(
+    labels: &[Label],
+    lines: &[&str],
+    content: &str,
+    gutter: usize,
+    color: bool,
+) -> String {
+    let mut shown: Vec<usize> = labels
+        .iter()
+        .flat_map(|label| {
+            let line = label.token.line;
+            line.saturating_sub(CONTEXT)..=(line + CONTEXT).min(lines.len().saturating_sub(1))
+        })
+        .collect();
+
+    shown.sort_unstable();
+    shown.dedup();
+
+    let mut result = String::new();
+
+    for (index, &number) in shown.iter().enumerate() {
+        if index != 0 && number > shown[index - 1] + 1 {
+            result += &(paint(color, BLUE, "...") + "\n");
         }
+
+        let line = lines.get(number).copied().unwrap_or("");
+        let line_number = format!("{:>width$} |", number + 1, width = gutter);
+
+        result += &paint(color, BLUE, &line_number);
+        result += &match line {
+            "" => "\n".to_string(),
+            _ => format!(" {}\n", line),
+        };
+
+        let mut line_labels: Vec<&Label> = labels
+            .iter()
+            .filter(|label| label.token.line == number)
+            .collect();
+        line_labels.sort_by_key(|label| label.token.col);
+
+        for label in line_labels {
+            let underline = match label.text {
+                "" => get_underline(label.token, content, label.mark),
+                text => get_underline(label.token, content, label.mark) + " " + text,
+            };
+
+            result += &paint(color, BLUE, &format!("{:width$} |", "", width = gutter));
+            result += &format!(
+                " {}{}\n",
+                get_padding(line, label.token.col),
+                paint(color, label.style, &underline)
+            );
+        }
+    }
+
+    result
+}
+
+fn render_heading(message: &str, color: bool) -> String {
+    format!(
+        "{}: {}\n",
+        paint(color, BOLD_RED, "error"),
+        paint(color, BOLD, message)
+    )
+}
+
+// The index of the line `offset` is on and the offset the line starts at
+fn get_line_start(content: &str, offset: usize) -> (usize, usize) {
+    let start = content[..offset].rfind(['\n', '\r']).map_or(0, |i| i + 1);
+
+    (get_lines(&content[..start]).len(), start)
+}
+
+/// The 0-based line and column, in characters, of `offset` in `content`
+pub fn get_position(content: &str, offset: usize) -> (usize, usize) {
+    let (line, start) = get_line_start(content, offset);
+
+    (line, content[start..offset].chars().count())
+}
+
+fn render_suggestion(
+    suggestion: &Suggestion,
+    lines: &[&str],
+    content: &str,
+    gutter: usize,
+    color: bool,
+) -> String {
+    let heading = format!(
+        "{}: {}\n",
+        paint(color, BOLD, "help"),
+        diagnostic::get_suggestion_message(suggestion)
+    );
+
+    let edit = suggestion.edit(content);
+    let (number, start) = get_line_start(content, edit.start);
+    let line = lines.get(number).copied().unwrap_or("");
+
+    // Only edits within a single line are shown
+    if edit.end > start + line.len() {
+        return heading;
     }
 
-    None
+    let (before, after) = (&line[..edit.start - start], &line[edit.end - start..]);
+    let (fixed, marks) = match edit.replacement.as_str() {
+        "" => (
+            line.to_string(),
+            &line[edit.start - start..edit.end - start],
+        ),
+        replacement => (before.to_string() + replacement + after, replacement),
+    };
+
+    let (mark, style) = match edit.replacement.is_empty() {
+        true => ("-", RED),
+        false => ("+", GREEN),
+    };
+    let width: usize = marks.chars().map(|c| c.width().unwrap_or(0)).sum();
+
+    let empty_gutter = paint(color, BLUE, &format!("{:width$} |", "", width = gutter));
+    let line_number = format!("{:>width$} |", number + 1, width = gutter);
+
+    [
+        heading,
+        format!("{}\n", empty_gutter),
+        format!("{} {}\n", paint(color, BLUE, &line_number), fixed),
+        format!(
+            "{} {}{}\n",
+            empty_gutter,
+            get_padding(&fixed, before.chars().count()),
+            paint(color, style, &mark.repeat(width.max(1)))
+        ),
+    ]
+    .concat()
 }
 
-fn eprint_location(token: &lexer::Token, content: &str) {
-    let line = match get_line(content, token.line) {
-        None => return,
-        Some(line) => line,
+fn render_parse_error(
+    parse_error: &ParseError,
+    content: &str,
+    file_path: &Path,
+    color: bool,
+) -> String {
+    let mut result = render_heading(&diagnostic::get_message(parse_error), color);
+    let mut labels = vec![];
+
+    if let Some(token) = parse_error.token {
+        labels.push(Label {
+            token,
+            mark: '^',
+            style: CYAN,
+            text: "",
+        });
+    }
+
+    if let (Some(related), Some(text)) = (
+        parse_error.related,
+        diagnostic::get_related_label(parse_error),
+    ) {
+        labels.push(Label {
+            token: related,
+            mark: '-',
+            style: BLUE,
+            text,
+        });
+    }
+
+    let lines = get_lines(content);
+    let suggestion_line = parse_error
+        .suggestion
+        .as_ref()
+        .map(|suggestion| suggestion.token.line);
+    let last_line = labels
+        .iter()
+        .map(|label| label.token.line + CONTEXT)
+        .chain(suggestion_line)
+        .max()
+        .unwrap_or(0)
+        .min(lines.len().saturating_sub(1));
+    let gutter = (last_line + 1).to_string().len();
+
+    let location = match parse_error.token {
+        Some(token) => format!(
+            "{}:{}:{}",
+            file_path.display(),
+            token.line + 1,
+            token.col + 1
+        ),
+        None => file_path.display().to_string(),
     };
 
-    let hint_carrot = " ".repeat(token.col) + "^";
+    result += &format!(
+        "{:width$}{} {}\n",
+        "",
+        paint(color, BLUE, "-->"),
+        paint(color, BOLD, &location),
+        width = gutter
+    );
+
+    if !labels.is_empty() {
+        result += &(paint(color, BLUE, &format!("{:width$} |", "", width = gutter)) + "\n");
+        result += &render_snippet(&labels, &lines, content, gutter, color);
+    }
 
-    eprintln!("{}", line);
-    eprintln!("{}", hint_carrot);
+    if let Some(suggestion) = &parse_error.suggestion {
+        result += &render_suggestion(suggestion, &lines, content, gutter, color);
+    }
+
+    result
 }
 
-pub fn eprint_parse_error(parse_error: ParseError, content: &str, file_path: &Path) {
-    match parse_error.token {
-        Some(token) => {
-            eprintln!(
-                "At {}:{}:{}",
-                file_path.display(),
-                token.line + 1,
-                token.col + 1
-            );
-            eprint_location(token, content);
+pub fn eprint_error(message: &str, color: bool) {
+    eprint!("{}", render_heading(message, color));
+}
+
+pub fn eprint_parse_errors(
+    parse_errors: &[ParseError],
+    content: &str,
+    file_path: &Path,
+    color: bool,
+) {
+    for (index, parse_error) in parse_errors.iter().enumerate() {
+        if index != 0 {
+            eprintln!();
         }
-        None => eprintln!("At {}", file_path.display()),
+
+        eprint!(
+            "{}",
+            render_parse_error(parse_error, content, file_path, color)
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use joxide::{lexer, parser};
+
+    #[test]
+    fn test_padding() {
+        assert_eq!(get_padding("{\"a\": 1}", 6), "      ");
+        assert_eq!(get_padding("\t\"é\": x", 6), "\t     ");
+        assert_eq!(get_padding("[\"日本\", x]", 6), "        ");
+        assert_eq!(get_padding("[\"🦀\", x]", 5), "      ");
+    }
+
+    #[test]
+    fn test_get_lines() {
+        assert_eq!(get_lines("a\nb\r\nc\rd"), vec!["a", "b", "c", "d"]);
+        assert_eq!(get_lines("a\n\nb\n"), vec!["a", "", "b"]);
     }
 
-    eprintln!("{}", diagnostic::get_message(&parse_error));
+    #[test]
+    fn test_render_parse_error() {
+        let raw = "{\n  \"abc\": 1,\n  \"b\": 2,\n  \"c\": 3,\n  \"d\": 4,\n  \"abc\": 5\n}\n";
+        let tokens = lexer::lex(raw);
+        let parse_errors = parser::parse(&tokens).unwrap_err();
+
+        let expected = [
+            "error: Duplicate keys are not valid\n",
+            " --> test.json:6:3\n",
+            "  |\n",
+            "1 | {\n",
+            "2 |   \"abc\": 1,\n",
+            "  |   ----- first defined here\n",
+            "3 |   \"b\": 2,\n",
+            "...\n",
+            "5 |   \"d\": 4,\n",
+            "6 |   \"abc\": 5\n",
+            "  |   ^^^^^\n",
+            "7 | }\n",
+        ];
+
+        assert_eq!(
+            render_parse_error(&parse_errors[0], raw, Path::new("test.json"), false),
+            expected.concat()
+        );
+
+        let raw = "";
+        let tokens = lexer::lex(raw);
+        let parse_errors = parser::parse(&tokens).unwrap_err();
+
+        assert_eq!(
+            render_parse_error(&parse_errors[0], raw, Path::new("test.json"), false),
+            "error: File ended unexpectedly\n --> test.json\n"
+        );
+
+        let raw = "{\"a\": [1, 2";
+        let tokens = lexer::lex(raw);
+        let parse_errors = parser::parse(&tokens).unwrap_err();
+        let expected = [
+            "error: File ended unexpectedly\n",
+            " --> test.json\n",
+            "  |\n",
+            "1 | {\"a\": [1, 2\n",
+            "  |       - the array starts here\n",
+            "help: close the array with ']'\n",
+            "  |\n",
+            "1 | {\"a\": [1, 2]\n",
+            "  |            +\n",
+        ];
+
+        assert_eq!(
+            render_parse_error(&parse_errors[0], raw, Path::new("test.json"), false),
+            expected.concat()
+        );
+
+        let raw = "[\n  1\n  2\n]";
+        let tokens = lexer::lex(raw);
+        let parse_errors = parser::parse(&tokens).unwrap_err();
+        let expected = [
+            "error: Did not expect '2', expected ']'\n",
+            " --> test.json:3:3\n",
+            "  |\n",
+            "1 | [\n",
+            "  | - the array starts here\n",
+            "2 |   1\n",
+            "3 |   2\n",
+            "  |   ^\n",
+            "4 | ]\n",
+            "help: insert a comma\n",
+            "  |\n",
+            "2 |   1,\n",
+            "  |    +\n",
+        ];
+
+        assert_eq!(
+            render_parse_error(&parse_errors[0], raw, Path::new("test.json"), false),
+            expected.concat()
+        );
+
+        let raw = "[1,]";
+        let tokens = lexer::lex(raw);
+        let parse_errors = parser::parse(&tokens).unwrap_err();
+        let expected = [
+            "\x1b[1;31merror\x1b[0m: \x1b[1mTrailing commas are not valid\x1b[0m\n",
+            " \x1b[34m-->\x1b[0m \x1b[1mtest.json:1:3\x1b[0m\n",
+            "\x1b[34m  |\x1b[0m\n",
+            "\x1b[34m1 |\x1b[0m [1,]\n",
+            "\x1b[34m  |\x1b[0m   \x1b[36m^\x1b[0m\n",
+            "\x1b[1mhelp\x1b[0m: remove the trailing comma\n",
+            "\x1b[34m  |\x1b[0m\n",
+            "\x1b[34m1 |\x1b[0m [1,]\n",
+            "\x1b[34m  |\x1b[0m   \x1b[31m-\x1b[0m\n",
+        ];
+
+        assert_eq!(
+            render_parse_error(&parse_errors[0], raw, Path::new("test.json"), true),
+            expected.concat()
+        );
+    }
 }
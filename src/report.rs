@@ -0,0 +1,362 @@
+use std::{borrow::Cow, path::Path};
+
+use crate::args::ErrorFormatArg;
+use joxide::{
+    diagnostic, formatter,
+    parser::{Json, ParseError},
+    Number,
+};
+use pretty::{eprint_error, eprint_parse_errors, get_position};
+
+struct Fix {
+    message: String,
+    start: (usize, usize),
+    end: (usize, usize),
+    replacement: String,
+}
+
+struct Diagnostic {
+    file: String,
+    position: Option<(usize, usize)>,
+    error_type: String,
+    message: String,
+    fix: Option<Fix>,
+}
+
+impl Diagnostic {
+    fn new(parse_error: &ParseError, content: &str, file_path: &Path) -> Diagnostic {
+        let one_based = |(line, col): (usize, usize)| (line + 1, col + 1);
+
+        let fix = parse_error.suggestion.as_ref().map(|suggestion| {
+            let edit = suggestion.edit(content);
+
+            Fix {
+                message: diagnostic::get_suggestion_message(suggestion).to_string(),
+                start: one_based(get_position(content, edit.start)),
+                end: one_based(get_position(content, edit.end)),
+                replacement: edit.replacement,
+            }
+        });
+
+        Diagnostic {
+            file: file_path.display().to_string(),
+            position: parse_error.position().map(one_based),
+            error_type: format!("{:?}", parse_error.error_type),
+            message: diagnostic::get_message(parse_error),
+            fix,
+        }
+    }
+}
+
+pub struct Reporter {
+    error_format: ErrorFormatArg,
+    color: bool,
+    results: Vec<Json<'static>>,
+    notifications: Vec<Json<'static>>,
+}
+
+fn string(s: &str) -> Json<'static> {
+    Json::String(Cow::Owned(s.to_string()))
+}
+
+fn number(n: usize) -> Json<'static> {
+    Json::Number(Number::from(n as u64))
+}
+
+fn object(members: Vec<(&str, Json<'static>)>) -> Json<'static> {
+    Json::Object(
+        members
+            .into_iter()
+            .map(|(key, value)| (Cow::Owned(key.to_string()), value))
+            .collect(),
+    )
+}
+
+fn fix_to_json(fix: Fix) -> Json<'static> {
+    object(vec![
+        ("message", string(&fix.message)),
+        ("line", number(fix.start.0)),
+        ("column", number(fix.start.1)),
+        ("end_line", number(fix.end.0)),
+        ("end_column", number(fix.end.1)),
+        ("replacement", string(&fix.replacement)),
+    ])
+}
+
+fn to_json(diagnostic: Diagnostic) -> Json<'static> {
+    let (line, column) = match diagnostic.position {
+        Some((line, column)) => (number(line), number(column)),
+        None => (Json::Null, Json::Null),
+    };
+
+    object(vec![
+        ("file", string(&diagnostic.file)),
+        ("line", line),
+        ("column", column),
+        ("error_type", string(&diagnostic.error_type)),
+        ("message", string(&diagnostic.message)),
+        ("suggestion", diagnostic.fix.map_or(Json::Null, fix_to_json)),
+    ])
+}
+
+fn artifact_location(file: &str) -> (&'static str, Json<'static>) {
+    (
+        "artifactLocation",
+        object(vec![("uri", string(&file.replace('\\', "/")))]),
+    )
+}
+
+fn to_sarif_result(diagnostic: Diagnostic) -> Json<'static> {
+    let mut physical_location = vec![artifact_location(&diagnostic.file)];
+
+    if let Some((line, column)) = diagnostic.position {
+        physical_location.push((
+            "region",
+            object(vec![
+                ("startLine", number(line)),
+                ("startColumn", number(column)),
+            ]),
+        ));
+    }
+
+    object(vec![
+        ("ruleId", string(&diagnostic.error_type)),
+        ("level", string("error")),
+        (
+            "message",
+            object(vec![("text", string(&diagnostic.message))]),
+        ),
+        (
+            "locations",
+            Json::Array(vec![object(vec![(
+                "physicalLocation",
+                object(physical_location),
+            )])]),
+        ),
+    ])
+}
+
+fn to_sarif_notification(message: &str, file: Option<&str>) -> Json<'static> {
+    let mut notification = vec![
+        ("level", string("error")),
+        ("message", object(vec![("text", string(message))])),
+    ];
+
+    if let Some(file) = file {
+        notification.push((
+            "locations",
+            Json::Array(vec![object(vec![(
+                "physicalLocation",
+                object(vec![artifact_location(file)]),
+            )])]),
+        ));
+    }
+
+    object(notification)
+}
+
+// Errors that are not about the content of a file go in the invocation, the
+// run has failed when there are any
+fn sarif_log(results: Vec<Json<'static>>, notifications: Vec<Json<'static>>) -> Json<'static> {
+    let driver = object(vec![
+        ("name", string("joxide")),
+        ("version", string(env!("CARGO_PKG_VERSION"))),
+    ]);
+
+    let invocation = object(vec![
+        ("executionSuccessful", Json::Bool(notifications.is_empty())),
+        ("toolExecutionNotifications", Json::Array(notifications)),
+    ]);
+
+    let run = object(vec![
+        ("tool", object(vec![("driver", driver)])),
+        ("invocations", Json::Array(vec![invocation])),
+        ("columnKind", string("unicodeCodePoints")),
+        ("results", Json::Array(results)),
+    ]);
+
+    object(vec![
+        (
+            "$schema",
+            string("https://json.schemastore.org/sarif-2.1.0.json"),
+        ),
+        ("version", string("2.1.0")),
+        ("runs", Json::Array(vec![run])),
+    ])
+}
+
+fn escape_github(s: &str, property: bool) -> String {
+    let escaped = s
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A");
+
+    match property {
+        true => escaped.replace(':', "%3A").replace(',', "%2C"),
+        false => escaped,
+    }
+}
+
+fn to_github(diagnostic: Diagnostic) -> String {
+    let mut properties = format!("file={}", escape_github(&diagnostic.file, true));
+
+    if let Some((line, column)) = diagnostic.position {
+        properties += &format!(",line={},col={}", line, column);
+    }
+
+    format!(
+        "::error {},title={}::{}",
+        properties,
+        escape_github(&diagnostic.error_type, true),
+        escape_github(&diagnostic.message, false)
+    )
+}
+
+fn to_github_error(message: &str, file: Option<&str>) -> String {
+    let properties = match file {
+        Some(file) => format!(" file={}", escape_github(file, true)),
+        None => String::new(),
+    };
+
+    format!("::error{}::{}", properties, escape_github(message, false))
+}
+
+impl Reporter {
+    pub fn new(error_format: ErrorFormatArg, color: bool) -> Reporter {
+        Reporter {
+            error_format,
+            color,
+            results: vec![],
+            notifications: vec![],
+        }
+    }
+
+    /// Reports an error that is not about the content of a file, like a file
+    /// that can't be read, `file_path` is the file it is about if any
+    pub fn error(&mut self, message: &str, file_path: Option<&Path>) {
+        let file = file_path.map(|file_path| file_path.display().to_string());
+
+        match self.error_format {
+            ErrorFormatArg::Human => eprint_error(message, self.color),
+            ErrorFormatArg::Json => {
+                let options = formatter::FormatOptions {
+                    indent_length: 0,
+                    ..formatter::FormatOptions::default()
+                };
+                let error = object(vec![
+                    ("file", file.as_deref().map_or(Json::Null, string)),
+                    ("message", string(message)),
+                ]);
+
+                eprint!("{}", formatter::format_json(error, &options))
+            }
+            ErrorFormatArg::Sarif => self
+                .notifications
+                .push(to_sarif_notification(message, file.as_deref())),
+            ErrorFormatArg::Github => eprintln!("{}", to_github_error(message, file.as_deref())),
+        }
+    }
+
+    pub fn report(&mut self, parse_errors: &[ParseError], content: &str, file_path: &Path) {
+        let options = formatter::FormatOptions {
+            indent_length: 0,
+            ..formatter::FormatOptions::default()
+        };
+
+        let diagnostics = parse_errors
+            .iter()
+            .map(|parse_error| Diagnostic::new(parse_error, content, file_path));
+
+        match self.error_format {
+            ErrorFormatArg::Human => {
+                eprint_parse_errors(parse_errors, content, file_path, self.color)
+            }
+            ErrorFormatArg::Json => diagnostics.for_each(|diagnostic| {
+                eprint!("{}", formatter::format_json(to_json(diagnostic), &options))
+            }),
+            ErrorFormatArg::Sarif => self.results.extend(diagnostics.map(to_sarif_result)),
+            ErrorFormatArg::Github => {
+                diagnostics.for_each(|diagnostic| eprintln!("{}", to_github(diagnostic)))
+            }
+        }
+    }
+
+    pub fn finish(self) {
+        if self.error_format == ErrorFormatArg::Sarif {
+            let options = formatter::FormatOptions {
+                indent_length: 2,
+                ..formatter::FormatOptions::default()
+            };
+
+            eprint!(
+                "{}",
+                formatter::format_json(sarif_log(self.results, self.notifications), &options)
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use joxide::{lexer, parser};
+
+    #[test]
+    fn test_diagnostics() {
+        let raw = "{\"a\": [1,],\n\"b\" 2}";
+        let tokens = lexer::lex(raw);
+        let parse_errors = parser::parse(&tokens).unwrap_err();
+        let options = formatter::FormatOptions {
+            indent_length: 0,
+            ..formatter::FormatOptions::default()
+        };
+
+        let diagnostic = Diagnostic::new(&parse_errors[0], raw, Path::new("dir/a,b.json"));
+        assert_eq!(
+            formatter::format_json(to_json(diagnostic), &options),
+            concat!(
+                "{\"file\":\"dir/a,b.json\",\"line\":1,\"column\":9,\"error_type\":\"TrailingComma\",",
+                "\"message\":\"Trailing commas are not valid\",\"suggestion\":{\"message\":\"remove the trailing comma\",",
+                "\"line\":1,\"column\":9,\"end_line\":1,\"end_column\":10,\"replacement\":\"\"}}\n"
+            )
+        );
+
+        let diagnostic = Diagnostic::new(&parse_errors[1], raw, Path::new("dir/a,b.json"));
+        assert_eq!(
+            to_github(diagnostic),
+            "::error file=dir/a%2Cb.json,line=2,col=5,title=MissingColon::Missing a ':' separator"
+        );
+
+        let tokens = lexer::lex("[");
+        let parse_errors = parser::parse(&tokens).unwrap_err();
+        let diagnostic = Diagnostic::new(&parse_errors[0], "[", Path::new("a.json"));
+
+        assert_eq!(
+            formatter::format_json(sarif_log(vec![to_sarif_result(diagnostic)], vec![]), &options),
+            concat!(
+                "{\"$schema\":\"https://json.schemastore.org/sarif-2.1.0.json\",\"version\":\"2.1.0\",",
+                "\"runs\":[{\"tool\":{\"driver\":{\"name\":\"joxide\",\"version\":\"",
+                env!("CARGO_PKG_VERSION"),
+                "\"}},\"invocations\":[{\"executionSuccessful\":true,\"toolExecutionNotifications\":[]}],",
+                "\"columnKind\":\"unicodeCodePoints\",\"results\":[{\"ruleId\":\"UnexpectedEnd\",",
+                "\"level\":\"error\",\"message\":{\"text\":\"File ended unexpectedly\"},",
+                "\"locations\":[{\"physicalLocation\":{\"artifactLocation\":{\"uri\":\"a.json\"}}}]}]}]}\n"
+            )
+        );
+
+        let notification = to_sarif_notification("Unable to open file", Some("dir\\a.json"));
+
+        assert_eq!(
+            formatter::format_json(notification, &options),
+            concat!(
+                "{\"level\":\"error\",\"message\":{\"text\":\"Unable to open file\"},",
+                "\"locations\":[{\"physicalLocation\":{\"artifactLocation\":{\"uri\":\"dir/a.json\"}}}]}\n"
+            )
+        );
+        assert_eq!(
+            to_github_error("No files matching 'a,b'", Some("a,b")),
+            "::error file=a%2Cb::No files matching 'a,b'"
+        );
+        assert_eq!(to_github_error("50%", None), "::error::50%25");
+    }
+}
@@ -0,0 +1,154 @@
+use std::path::{Component, Path, PathBuf};
+
+use file_types;
+use glob::{MatchOptions, Pattern, PatternError};
+use ignore::WalkBuilder;
+
+/// Patterns given with --exclude or in a config file
+#[derive(Debug, Clone, Default)]
+pub struct Excludes {
+    patterns: Vec<Pattern>,
+    // The directory of the config file, paths are relative to it
+    dir: Option<PathBuf>,
+}
+
+impl Excludes {
+    pub fn new(patterns: &[String]) -> Result<Excludes, PatternError> {
+        let patterns = patterns
+            .iter()
+            .map(|pattern| Pattern::new(pattern.trim_start_matches("./")))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Excludes {
+            patterns,
+            dir: None,
+        })
+    }
+
+    /// Patterns from the config file in `dir`, the ones with a "/" match paths
+    /// relative to `dir`
+    pub fn in_dir(patterns: &[String], dir: &Path) -> Result<Excludes, PatternError> {
+        Ok(Excludes {
+            dir: Some(dir.to_path_buf()),
+            ..Excludes::new(patterns)?
+        })
+    }
+
+    /// Patterns without a "/" match the name of the path or of any directory
+    /// it is in, the other ones match the whole path
+    pub fn matches(&self, path: &Path) -> bool {
+        let options = MatchOptions {
+            require_literal_separator: true,
+            ..MatchOptions::new()
+        };
+
+        let absolute;
+        let path = match self.dir {
+            Some(ref dir) => {
+                absolute = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+                absolute.strip_prefix(dir).unwrap_or(&absolute)
+            }
+            None => path.strip_prefix(".").unwrap_or(path),
+        };
+        let full = path.to_string_lossy().replace('\\', "/");
+
+        self.patterns
+            .iter()
+            .any(|pattern| match pattern.as_str().contains('/') {
+                true => pattern.matches_with(&full, options),
+                false => path.components().any(|component| match component {
+                    Component::Normal(name) => {
+                        pattern.matches_with(&name.to_string_lossy(), options)
+                    }
+                    _ => false,
+                }),
+            })
+    }
+}
+
+/// How directories are searched for files
+pub struct WalkOptions {
+    pub excludes: Vec<Excludes>,
+    pub extensions: Vec<String>,
+    /// Whether to go into hidden directories
+    pub hidden: bool,
+}
+
+impl WalkOptions {
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        self.excludes.iter().any(|excludes| excludes.matches(path))
+    }
+}
+
+fn is_hidden_dir(entry: &ignore::DirEntry) -> bool {
+    entry.depth() > 0
+        && entry
+            .file_type()
+            .is_some_and(|file_type| file_type.is_dir())
+        && entry.file_name().to_string_lossy().starts_with('.')
+}
+
+/// JSON files in `dir` and its subdirectories, skipping hidden directories
+/// unless asked for, files ignored by .gitignore or .ignore and the excluded
+/// paths
+pub fn walk_dir(dir: &Path, walk_options: &WalkOptions) -> Vec<Result<PathBuf, String>> {
+    let excludes = walk_options.excludes.clone();
+    let hidden = walk_options.hidden;
+
+    WalkBuilder::new(dir)
+        .hidden(false)
+        .require_git(false)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .filter_entry(move |entry| {
+            (hidden || !is_hidden_dir(entry))
+                && !excludes
+                    .iter()
+                    .any(|excludes| excludes.matches(entry.path()))
+        })
+        .build()
+        .filter_map(|entry| match entry {
+            Ok(entry) => {
+                let is_file = entry
+                    .file_type()
+                    .is_some_and(|file_type| file_type.is_file());
+                let is_included = file_types::is_included(entry.path(), &walk_options.extensions);
+
+                (is_file && is_included).then(|| Ok(entry.into_path()))
+            }
+            Err(err) => Some(Err(format!("Unable to read directory, reason: {}", err))),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_excludes() {
+        let excludes = Excludes::new(&[
+            "node_modules".to_string(),
+            "*.min.json".to_string(),
+            "./data/generated/*.json".to_string(),
+        ])
+        .unwrap();
+
+        assert!(excludes.matches(Path::new("node_modules")));
+        assert!(excludes.matches(Path::new("a/node_modules/b/package.json")));
+        assert!(excludes.matches(Path::new("./dist/app.min.json")));
+        assert!(excludes.matches(Path::new("data/generated/a.json")));
+        assert!(excludes.matches(Path::new("./data/generated/a.json")));
+        assert!(!excludes.matches(Path::new("data/generated/nested/a.json")));
+        assert!(!excludes.matches(Path::new("data/a.json")));
+        assert!(!excludes.matches(Path::new("node_modules.json")));
+
+        assert!(Excludes::new(&["[".to_string()]).is_err());
+
+        let dir = std::path::absolute("config").unwrap();
+        let excludes = Excludes::in_dir(&["data/*.json".to_string()], &dir).unwrap();
+
+        assert!(excludes.matches(Path::new("config/data/a.json")));
+        assert!(excludes.matches(Path::new("./config/data/a.json")));
+        assert!(!excludes.matches(Path::new("data/a.json")));
+    }
+}
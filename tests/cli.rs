@@ -0,0 +1,427 @@
+extern crate joxide;
+
+use std::{
+    env, fs,
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Output, Stdio},
+};
+
+fn joxide(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_joxide"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn joxide_stdin(args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_joxide"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+
+    child.wait_with_output().unwrap()
+}
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("joxide-{}-{}", std::process::id(), name));
+
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn temp_file(dir: &Path, name: &str, content: &str) -> String {
+    let path = dir.join(name);
+
+    fs::write(&path, content).unwrap();
+    path.to_string_lossy().into_owned()
+}
+
+#[test]
+fn test_exit_codes() {
+    let dir = temp_dir("exit-codes");
+    let valid = temp_file(&dir, "valid.json", "{\n    \"a\": [\n        1\n    ]\n}\n");
+    let invalid = temp_file(&dir, "invalid.json", "{\"a\": [1 2]}");
+    let unformatted = temp_file(&dir, "unformatted.json", "{\"a\":[1]}");
+    let missing = dir.join("missing.json").to_string_lossy().into_owned();
+
+    let cases: Vec<(Vec<&str>, i32)> = vec![
+        (vec!["validate", &valid], 0),
+        (vec!["validate", &valid, &unformatted], 0),
+        (vec!["validate", &invalid], 1),
+        (vec!["validate", &invalid, &valid], 1),
+        (vec!["format", &valid], 0),
+        (vec!["format", "--check", &valid], 0),
+        (vec!["format", "--check", &unformatted], 1),
+        (vec!["format", "--diff", &unformatted], 1),
+        (vec!["format", "--check", &invalid], 1),
+        (vec!["validate", &missing], 2),
+        (vec!["validate", &invalid, &missing], 2),
+        (vec!["validate", "[", &valid], 2),
+        (vec!["validate", "--unknown", &valid], 2),
+        (vec!["lint", &valid], 2),
+        (vec!["--help"], 0),
+    ];
+
+    for (args, expected) in cases {
+        assert_eq!(joxide(&args).status.code(), Some(expected), "{:?}", args);
+    }
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_reports_every_file() {
+    let dir = temp_dir("every-file");
+    let first = temp_file(&dir, "first.json", "[1,]");
+    let second = temp_file(&dir, "second.json", "[01]");
+
+    let output = joxide(&["validate", &first, &second]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr.contains("first.json:1:3"), "{}", stderr);
+    assert!(stderr.contains("second.json:1:2"), "{}", stderr);
+
+    let output = joxide(&["format", "--check", &first, &second]);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_stdin() {
+    let output = joxide_stdin(&["format", "--indent-length", "2"], "{\"a\":[1]}");
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(output.stdout, b"{\n  \"a\": [\n    1\n  ]\n}\n");
+
+    let output = joxide_stdin(
+        &["format", "-", "--write", "--indent-length", "0"],
+        "[1, 2]",
+    );
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(output.stdout, b"[1,2]\n");
+
+    let output = joxide_stdin(&["validate", "--stdin-filename", "data.json", "-"], "[1,]");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr.contains(" --> data.json:1:3\n"), "{}", stderr);
+
+    let output = joxide_stdin(&["format", "--check"], "[1]");
+
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(output.stdout, b"<stdin>\n");
+}
+
+#[test]
+fn test_indent() {
+    let output = joxide_stdin(&["format", "--indent-style", "tab"], "{\"a\":[1]}");
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(output.stdout, b"{\n\t\"a\": [\n\t\t1\n\t]\n}\n");
+
+    let output = joxide_stdin(&["format", "--indent", "auto"], "{\n  \"a\":[1]}");
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(output.stdout, b"{\n  \"a\": [\n    1\n  ]\n}\n");
+
+    let output = joxide_stdin(
+        &["format", "--indent", "auto", "--indent-length", "1"],
+        "{\"a\":[1]}",
+    );
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(output.stdout, b"{\n \"a\": [\n  1\n ]\n}\n");
+}
+
+#[test]
+fn test_error_format() {
+    let dir = temp_dir("error-format");
+    let first = temp_file(&dir, "first.json", "[1,]");
+    let second = temp_file(&dir, "second.json", "{\"a\" 1}");
+
+    let output = joxide(&["validate", "--error-format", "sarif", &first, &second]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let tokens = joxide::lex(&stderr);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(joxide::parse(&tokens).is_ok(), "{}", stderr);
+    assert_eq!(stderr.matches("\"ruleId\"").count(), 2);
+
+    let output = joxide(&["validate", "--error-format", "github", &first, &second]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stderr.lines().count(), 2);
+    assert!(stderr.lines().all(|line| line.starts_with("::error file=")));
+
+    // Errors stay out of the formatted output
+    let valid = temp_file(&dir, "valid.json", "[1]");
+
+    for error_format in ["sarif", "github", "json"] {
+        let output = joxide(&["format", "--error-format", error_format, &valid, &first]);
+
+        assert_eq!(output.status.code(), Some(1));
+        assert_eq!(output.stdout, b"[\n    1\n]\n", "{}", error_format);
+        assert!(!output.stderr.is_empty(), "{}", error_format);
+    }
+
+    let output = joxide(&["validate", "--error-format", "json", &second]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(
+        joxide::parse_lines(&joxide::lex(&stderr)).is_ok(),
+        "{}",
+        stderr
+    );
+
+    // Errors that are not about the content of a file use the format too
+    let missing = dir.join("missing.json").to_string_lossy().into_owned();
+    let args = |error_format| ["validate", "--error-format", error_format, &missing, &first];
+
+    let output = joxide(&args("sarif"));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let tokens = joxide::lex(&stderr);
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(joxide::parse(&tokens).is_ok(), "{}", stderr);
+    assert!(
+        stderr.contains("\"executionSuccessful\": false"),
+        "{}",
+        stderr
+    );
+    assert_eq!(stderr.matches("No such file").count(), 1);
+    assert_eq!(stderr.matches("\"ruleId\"").count(), 1);
+
+    let output = joxide(&args("json"));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let tokens = joxide::lex(&stderr);
+    let values = joxide::parse_lines(&tokens);
+
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(
+        values.map(|values| values.len()).ok(),
+        Some(2),
+        "{}",
+        stderr
+    );
+    assert!(stderr.starts_with("{\"file\":"), "{}", stderr);
+
+    let output = joxide(&args("github"));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(stderr.lines().count(), 2);
+    assert!(stderr.lines().all(|line| line.starts_with("::error")));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_color() {
+    let dir = temp_dir("color");
+    let invalid = temp_file(&dir, "invalid.json", "[1,]");
+    let unformatted = temp_file(&dir, "unformatted.json", "[1]");
+
+    let output = joxide(&["validate", &invalid]);
+    assert!(!output.stderr.contains(&b'\x1b'));
+
+    let output = joxide(&["validate", "--color", "always", &invalid]);
+    assert!(output.stderr.starts_with(b"\x1b[1;31merror\x1b[0m: "));
+
+    let output = joxide(&["format", "--diff", "--color", "never", &unformatted]);
+    assert!(!output.stdout.contains(&b'\x1b'));
+
+    let output = joxide(&["format", "--diff", "--color", "always", &unformatted]);
+    assert!(output.stdout.starts_with(b"\x1b[1m--- "));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_config() {
+    let dir = temp_dir("config");
+    let nested = dir.join("nested");
+    fs::create_dir_all(&nested).unwrap();
+
+    temp_file(
+        &dir,
+        ".joxide.json",
+        "{\n  \"indent-length\": 4,\n  \"overrides\": [{\"files\": \"package.json\", \"indent-length\": 2}]\n}\n",
+    );
+    let data = temp_file(&nested, "data.json", "{\"a\":[1]}");
+    let package = temp_file(&nested, "package.json", "{\"a\":[1]}");
+
+    let output = joxide(&["format", &data, &package]);
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        output.stdout,
+        b"{\n    \"a\": [\n        1\n    ]\n}\n{\n  \"a\": [\n    1\n  ]\n}\n"
+    );
+
+    let output = joxide(&["format", "--indent-length", "1", &package]);
+
+    assert_eq!(output.stdout, b"{\n \"a\": [\n  1\n ]\n}\n");
+
+    temp_file(&nested, "joxide.toml", "max-width = 80\n");
+
+    let output = joxide(&["format", &package]);
+
+    assert_eq!(output.stdout, b"{\"a\": [1]}\n");
+
+    temp_file(&nested, "joxide.toml", "max-width = \"80\"\n");
+
+    let output = joxide(&["format", &package]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(
+        stderr.contains("'max-width' should be a non-negative integer"),
+        "{}",
+        stderr
+    );
+
+    temp_file(&nested, "joxide.toml", "sort-keys = true\n");
+    let unsorted = temp_file(&nested, "unsorted.json", "{\"b\":1,\"a\":2}");
+
+    let output = joxide(&["format", &unsorted]);
+
+    assert_eq!(output.stdout, b"{\n    \"a\": 2,\n    \"b\": 1\n}\n");
+
+    let output = joxide(&["format", "--no-sort-keys", &unsorted]);
+
+    assert_eq!(output.stdout, b"{\n    \"b\": 1,\n    \"a\": 2\n}\n");
+
+    // The indent given on the command line wins over detecting it
+    temp_file(&nested, "joxide.toml", "indent = \"auto\"\n");
+    let indented = temp_file(&nested, "indented.json", "[\n  1\n]\n");
+
+    let output = joxide(&["format", &indented]);
+
+    assert_eq!(output.stdout, b"[\n  1\n]\n");
+
+    let output = joxide(&["format", "--indent-length", "8", &indented]);
+
+    assert_eq!(output.stdout, b"[\n        1\n]\n");
+
+    let output = joxide(&["format", "--indent-style", "tab", &indented]);
+
+    assert_eq!(output.stdout, b"[\n\t1\n]\n");
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_directory_walk() {
+    let dir = temp_dir("directory-walk");
+
+    for sub_dir in [".cache", "node_modules", "ignored", "kept"] {
+        fs::create_dir_all(dir.join(sub_dir)).unwrap();
+    }
+
+    temp_file(&dir, ".gitignore", "ignored/\n");
+    temp_file(&dir, ".ignore", "*.min.json\n");
+    temp_file(&dir.join(".cache"), "a.json", "[1,]");
+    temp_file(&dir.join("node_modules"), "a.json", "[1,]");
+    temp_file(&dir.join("ignored"), "a.json", "[1,]");
+    temp_file(&dir.join("kept"), "a.min.json", "[1,]");
+    let kept = temp_file(&dir.join("kept"), "a.json", "[1]");
+    let dir = dir.to_string_lossy().into_owned();
+
+    let output = joxide(&["validate", &dir]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stderr.matches("error: ").count(), 1, "{}", stderr);
+    assert!(stderr.contains("node_modules"), "{}", stderr);
+
+    let output = joxide(&["validate", "--exclude", "node_modules", &dir]);
+
+    assert_eq!(output.status.code(), Some(0));
+
+    let output = joxide(&["format", "--exclude", "*.json", &kept]);
+
+    assert_eq!(output.status.code(), Some(0));
+    assert!(output.stdout.is_empty());
+
+    let output = joxide(&["validate", "--exclude", "[", &kept]);
+
+    assert_eq!(output.status.code(), Some(2));
+
+    let output = joxide(&["validate", "--hidden", "--exclude", "node_modules", &dir]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stderr.matches("error: ").count(), 1, "{}", stderr);
+    assert!(stderr.contains(".cache"), "{}", stderr);
+
+    // The config of the directory adds its excludes and can search hidden ones
+    temp_file(
+        Path::new(&dir),
+        "joxide.toml",
+        "exclude = [\"kept/*.json\", \"node_modules\"]\nhidden = true\n",
+    );
+
+    let output = joxide(&["validate", &dir]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stderr.matches("error: ").count(), 1, "{}", stderr);
+    assert!(stderr.contains(".cache"), "{}", stderr);
+
+    let output = joxide(&["validate", "--no-hidden", &dir]);
+
+    assert_eq!(output.status.code(), Some(0));
+
+    let output = joxide(&["validate", "--no-hidden", "--ext", "data", &dir]);
+
+    assert_eq!(output.status.code(), Some(0));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_file_types() {
+    let dir = temp_dir("file-types");
+    temp_file(&dir, "a.jsonc", "// comment\n[1,]\n");
+    temp_file(&dir, "b.jsonl", "{\"a\": 1}\n[ 2 ]\n");
+    temp_file(&dir, ".babelrc", "{presets: ['env']}\n");
+    temp_file(&dir, "c.txt", "not json");
+    temp_file(&dir, "d.data", "[1,]");
+    let jsonl = dir.join("b.jsonl").to_string_lossy().into_owned();
+    let dir = dir.to_string_lossy().into_owned();
+
+    let output = joxide(&["validate", &dir]);
+
+    assert_eq!(output.status.code(), Some(0), "{:?}", output);
+
+    let output = joxide(&["format", &jsonl]);
+
+    assert_eq!(output.stdout, b"{\"a\":1}\n[2]\n");
+
+    let output = joxide(&["validate", "--ext", "data", &dir]);
+
+    assert_eq!(output.status.code(), Some(1));
+
+    let output = joxide(&["validate", "--ext", "data", "--dialect", "jsonc", &dir]);
+
+    assert_eq!(output.status.code(), Some(0));
+    fs::remove_dir_all(dir).unwrap();
+}